@@ -12,6 +12,8 @@ df = pd.read_csv(inputFile, sep=',', on_bad_lines='skip', index_col=False)
 baseColumns = ['R', 'Mutation Algorithm', 'Problem', 'Input Dim', 'Hidden Dim', 'Output Dim', 'Iteration',
        'Generation', 'Score', 'Solution']
 df.dropna(inplace=True, subset=baseColumns)
+# Newer exports append extra columns (seed, evaluation steps, objectives...), which are not used in the analysis
+df = df[baseColumns]
 df.drop(['Iteration', 'Solution', 'Input Dim', 'Hidden Dim'], axis=1, inplace=True);
 
 ################## MEDIAN
@@ -0,0 +1,224 @@
+use crate::experiment::{self, AlgorithmEnum, ExperimentConfig, ProblemEnum, QualityDiversity};
+use crate::mutationAlgorithms::crossover::Crossover;
+use crate::mutationAlgorithms::populationStrategy::Strategy;
+use crate::mutationAlgorithms::simulatedAnnealing::Cooling;
+use crate::mutationAlgorithms::restartStrategy::RestartMode;
+use crate::nn::activation::Activation;
+use crate::nn::ann::NANN;
+use crate::nn::networkFile::{NetworkFormat, NetworkMetadata};
+use crate::suite::{self, ExperimentSuite};
+
+pub const USAGE: &str = "Usage: thesis_project [options]
+Runs experiments without opening the graphical user interface. Options:
+    --problem <name>        Problem to solve, e.g. \"Sphere Continuous Quarter\"
+    --algorithm <name>      Mutation algorithm, e.g. \"Harmonic (1+1)NA\"
+    --strategy <strategy>   Evolution strategy the mutation algorithm is used in, \"(mu+lambda)\" or \"(mu,lambda)\",
+                            e.g. \"(5+10)\" or \"(1,10)\" (default: \"(1+1)\")
+    --crossover <name>      Runs a genetic algorithm with the mutation algorithm as its mutation step,
+                            recombining with \"uniform\", \"neuron\" or \"layer\" crossover
+    --population <n>        Population size of the genetic algorithm, novelty search and NSGA-II (default: 20)
+    --qd <name>             Runs the mutation algorithm in a quality-diversity runner, \"map-elites\" or \"novelty-search\"
+    --archive-cells <n>     Cells along each behaviour dimension of the quality-diversity archive (default: 20)
+    --nsga2                 Runs the mutation algorithm in NSGA-II, on the problem's separate objectives and the number of connections
+    --crossover-rate <p>    Probability of recombining two parents, for the genetic algorithm (default: 0.9)
+    --input <n>             Input dimension of the network
+    --hidden <widths>       Comma separated widths of the hidden layers, e.g. \"4,4,2\". Empty for no hidden layer
+    --output <n>            Output dimension of the network
+    --hidden-activation <names> Activation of every hidden layer, e.g. \"ReLU\", or a comma separated list with one per hidden layer,
+                            e.g. \"ReLU,Tanh\" (default: Identity)
+    --output-activation <name>  Activation of the output layer (default: Identity)
+    -r, --resolution <r>    Resolution parameter
+    --optimum <value>       Score at which an execution is considered a success
+    --iterations <n>        Number of times the experiment is ran
+    --max-generations <n>   Stops an iteration after n generations
+    --max-evaluations <n>   Stops an iteration after n problem evaluations
+    --max-time <seconds>    Stops an iteration after the given wall-clock time.
+                            Without any of these, iterations stop after 100 * R * log2(R) generations
+    --points <n>            Number of points, for the discrete sphere problems
+    --success <value>       Success multiplier, for the self-adaptive algorithms
+    --failure <value>       Failure multiplier, for the self-adaptive algorithms
+    --beta <value>          Power-law exponent, for the fast algorithms (default: 1.5)
+    --cooling <name>        Cooling schedule of simulated annealing: \"exponential\" (default), \"linear\" or \"logarithmic\"
+    --temperature <value>   Initial temperature of simulated annealing (default: 0.05)
+    --cooling-rate <value>  Factor the temperature is multiplied by every generation, for exponential cooling (default: 0.999)
+    --toggle-probability <value>
+                            Probability that the (1+1)NA family switches a connection on or off instead of changing its weight (default: 0)
+    --restart <mode>        Restart the algorithm when it stalls, from a new random network (\"reinitialise\") or a strongly perturbed one (\"perturb\")
+    --restart-patience <n>  Generations without improvement before restarting, doubled after every restart of --restart (default: 1000)
+    --no-bias               Creates the network without biases
+    --workers <n>           Number of threads iterations are distributed across (default: one per CPU core)
+    -o, --out <file>        CSV file the results are exported to
+    --trace <file>          Records every accepted mutation to a CSV file, or JSON Lines if the file ends in .jsonl
+    --archive <file>        Exports the final archive of the quality-diversity runners to a CSV file, one row per filled cell
+    --pareto <file>         Exports the final Pareto front of NSGA-II to a CSV file, one row per member
+    --save-networks <dir>   Saves the best network of every iteration to the given directory
+    --network-format <name> Format networks are saved in, \"binary\" (default) or \"json\"
+    --load-network <file>   Starts every iteration from a saved network instead of a random one,
+                            taking the network dimensions, activations and bias from it,
+                            and its resolution and optimum unless -r or --optimum is given
+    --seed <n>              Master seed all random sources are derived from (default: random)
+    --replay <iteration>    Runs only the given iteration, reproducing it exactly when combined with its --seed
+    --evaluate <file>       Evaluates a saved network on --problem and prints a report of its predictions,
+                            using the problem instance of iteration --replay (default 1) of --seed
+    --suite <file>          Runs every experiment described in a TOML or JSON suite file.
+                            Only --out and --workers can be combined with it, overriding the suite's values
+    --list                  Lists the available problems, algorithms, crossover operators, quality-diversity runners and activations
+    -h, --help              Shows this message";
+
+/*
+    Entry point for headless execution. Parses the command line arguments (excluding the program name),
+    runs the experiments and prints each iteration's results to the standard output.
+ */
+pub fn run(args: Vec<String>) -> Result<(), String> {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--list") {
+        println!("Problems:");
+        ProblemEnum::all().iter().for_each(|(name, _)| println!("    {}", name));
+        println!("Mutation Algorithms:");
+        AlgorithmEnum::all().iter().for_each(|(name, _)| println!("    {}", name));
+        println!("Crossover Operators:");
+        Crossover::all().iter().for_each(|(name, _)| println!("    {}", name));
+        println!("Quality-Diversity Runners:");
+        QualityDiversity::all().iter().for_each(|(name, _)| println!("    {}", name));
+        println!("Activations:");
+        Activation::all().iter().for_each(|(name, _)| println!("    {}", name));
+        return Ok(());
+    }
+    if let Some(position) = args.iter().position(|a| a == "--suite") {
+        return runSuiteFile(&args, position);
+    }
+    if let Some(position) = args.iter().position(|a| a == "--evaluate") {
+        return evaluateNetwork(&args, position);
+    }
+    let config = parseArguments(&args)?;
+    for line in config.describe() {
+        println!("{}", line);
+    }
+    experiment::runExperiments(&config, |result| {
+        println!("Iteration {}/{}: finished in generation {} ({} evaluations) with a score of {}{}", result.iteration, config.iterationRange().end - 1, result.generation, result.evaluations, result.evaluation.score, result.restartsSummary());
+    });
+    Ok(())
+}
+
+fn runSuiteFile(args: &[String], position: usize) -> Result<(), String> {
+    let path = args.get(position + 1).ok_or("Missing value for --suite".to_string())?;
+    let suite = ExperimentSuite::load(path)?;
+    let mut outputFile = suite.output.clone();
+    let mut workers = suite.workers;
+    let mut remaining = args.iter().enumerate().filter(|(i, _)| *i != position && *i != position + 1).map(|(_, a)| a);
+    while let Some(arg) = remaining.next() {
+        match arg.as_str() {
+            "-o" | "--out" => outputFile = Some(remaining.next().ok_or(format!("Missing value for {}", arg))?.clone()),
+            "--workers" => workers = parseValue(arg, remaining.next().ok_or(format!("Missing value for {}", arg))?)?,
+            _ => return Err(format!("Argument {} cannot be combined with --suite", arg))
+        }
+    }
+    let configs = suite.expand()?;
+    println!("Running suite {} with {} experiments (seed {})", path, configs.len(), configs.first().map_or(0, |c| c.seed));
+    suite::runSuite(&configs, workers, outputFile.as_deref(), |config, result| {
+        println!("{}, {}, R {}: iteration {}/{} finished in generation {} with a score of {}{}",
+            result.problemName, result.algorithmName, config.r, result.iteration, config.iterationRange().end - 1, result.generation, result.evaluation.score, result.restartsSummary());
+    });
+    Ok(())
+}
+
+/*
+    Loads a saved network and evaluates it on the configured problem, printing the problem's report.
+    The problem instance (discrete points, cartpole starting state) is the one the given seed and iteration were ran with.
+ */
+fn evaluateNetwork(args: &[String], position: usize) -> Result<(), String> {
+    let path = args.get(position + 1).ok_or("Missing value for --evaluate".to_string())?;
+    let mut remaining: Vec<String> = args.to_vec();
+    remaining.drain(position..position + 2);
+    remaining.extend(["--load-network".to_string(), path.clone()]);
+    let config = parseArguments(&remaining)?;
+    let (nn, problem, _) = experiment::setupIteration(&config, config.firstIteration);
+    println!("Network: {} (seed {}, iteration {})", path, config.seed, config.firstIteration);
+    print!("{}", problem.render(&nn, &config.context()));
+    Ok(())
+}
+
+pub fn parseArguments(args: &[String]) -> Result<ExperimentConfig, String> {
+    let mut config = ExperimentConfig::default();
+    let mut replay: Option<u32> = None;
+    let mut initialNetwork: Option<(NANN, Option<NetworkMetadata>)> = None;
+    let mut resolution: Option<f64> = None;
+    let mut optimum: Option<f64> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--no-bias" {
+            config.bias = false;
+            continue;
+        }
+        if arg == "--nsga2" {
+            config.multiObjective = true;
+            continue;
+        }
+        let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--problem" => config.problem = ProblemEnum::fromName(value).ok_or(format!("Unknown problem: {}", value))?,
+            "--algorithm" => config.algorithm = AlgorithmEnum::fromName(value).ok_or(format!("Unknown mutation algorithm: {}", value))?,
+            "--strategy" => config.strategy = Strategy::fromName(value)?,
+            "--crossover" => config.crossover = Some(Crossover::fromName(value).ok_or(format!("Unknown crossover: {}", value))?),
+            "--population" => config.populationSize = parseValue(arg, value)?,
+            "--qd" => config.qualityDiversity = Some(QualityDiversity::fromName(value).ok_or(format!("Unknown quality-diversity runner: {}", value))?),
+            "--archive-cells" => config.archiveCells = parseValue(arg, value)?,
+            "--crossover-rate" => config.crossoverRate = parseValue(arg, value)?,
+            "--input" => config.inputDim = parseValue(arg, value)?,
+            "--hidden" => config.hiddenDims = experiment::parseHiddenDims(value)?,
+            "--output" => config.outputDim = parseValue(arg, value)?,
+            "--hidden-activation" => config.hiddenActivations = experiment::parseActivations(value)?,
+            "--output-activation" => config.outputActivation = Activation::fromName(value).ok_or(format!("Unknown activation: {}", value))?,
+            "-r" | "--resolution" => resolution = Some(parseValue(arg, value)?),
+            "--optimum" => optimum = Some(parseValue(arg, value)?),
+            "--iterations" => config.iterations = parseValue(arg, value)?,
+            "--max-generations" => config.maxGenerations = Some(parseValue(arg, value)?),
+            "--max-evaluations" => config.maxEvaluations = Some(parseValue(arg, value)?),
+            "--max-time" => config.maxTime = Some(parseValue(arg, value)?),
+            "--points" => config.numberOfPoints = parseValue(arg, value)?,
+            "--success" => config.successAdaptation = parseValue(arg, value)?,
+            "--failure" => config.failureAdaptation = parseValue(arg, value)?,
+            "--beta" => config.beta = parseValue(arg, value)?,
+            "--cooling" => config.coolingSchedule = Cooling::fromName(value).ok_or(format!("Unknown cooling schedule: {}", value))?,
+            "--temperature" => config.initialTemperature = parseValue(arg, value)?,
+            "--cooling-rate" => config.coolingRate = parseValue(arg, value)?,
+            "--toggle-probability" => config.connectionToggleProbability = parseValue(arg, value)?,
+            "--restart" => config.restart = Some(RestartMode::fromName(value).ok_or(format!("Unknown restart mode: {}", value))?),
+            "--restart-patience" => config.restartPatience = parseValue(arg, value)?,
+            "--workers" => config.workers = parseValue(arg, value)?,
+            "--seed" => config.seed = parseValue(arg, value)?,
+            "--replay" => replay = Some(parseValue(arg, value)?),
+            "-o" | "--out" => config.outputFile = Some(value.clone()),
+            "--trace" => config.traceFile = Some(value.clone()),
+            "--archive" => config.archiveFile = Some(value.clone()),
+            "--pareto" => config.paretoFile = Some(value.clone()),
+            "--save-networks" => config.networkDirectory = Some(value.clone()),
+            "--network-format" => config.networkFormat = NetworkFormat::fromName(value).ok_or(format!("Unknown network format: {}", value))?,
+            "--load-network" => initialNetwork = Some(NANN::load(value)?),
+            _ => return Err(format!("Unknown argument: {}", arg))
+        }
+    }
+    if let Some(iteration) = replay {
+        config.firstIteration = iteration;
+        config.iterations = 1;
+    }
+    if let Some((nn, metadata)) = initialNetwork {
+        // A saved network keeps the resolution and optimum it was evolved with, unless they're given explicitly
+        if let Some(metadata) = metadata {
+            config.r = metadata.resolution;
+            config.optimum = metadata.optimum;
+        }
+        config = config.withInitialNetwork(nn);
+    }
+    config.r = resolution.unwrap_or(config.r);
+    config.optimum = optimum.unwrap_or(config.optimum);
+    config.validate()?;
+    Ok(config)
+}
+
+fn parseValue<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value for {}: {}", arg, value))
+}
@@ -0,0 +1,1043 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use druid::Data;
+use serde::Deserialize;
+
+use crate::mutationAlgorithms::mutationAlgorithm::{ArchiveCell, MutationAlgorithm, MutationResult, ParetoPoint};
+use crate::mutationAlgorithms::{gaussianMutation::GaussianMutation, SALocalMutation::SALocalMutation, SAOnePlusOneNA::SAOnePlusOneNA, localOnePlusOneNA::LocalOnePlusOneNA, onePlusOneNA::OnePlusOneNA};
+use crate::mutationAlgorithms::populationStrategy::{PopulationStrategy, Strategy};
+use crate::mutationAlgorithms::{crossover::Crossover, geneticAlgorithm::GeneticAlgorithm, CMAES::CMAES, adaptiveGaussianMutation::AdaptiveGaussianMutation, fastOnePlusOneNA::FastOnePlusOneNA};
+use crate::mutationAlgorithms::{simulatedAnnealing::{Cooling, CoolingSchedule, SimulatedAnnealing}, randomLocalSearch::RandomLocalSearch, restartStrategy::{RestartMode, RestartStrategy}};
+use crate::mutationAlgorithms::{mapElites::MapElites, noveltySearch::NoveltySearch, NSGAII::NSGAII};
+use crate::nn::activation::Activation;
+use crate::nn::networkFile::{NetworkFormat, NetworkMetadata};
+use crate::nn::ann::{NANN, RunObserver, self};
+use crate::problems::cartpoleProblem::CartpoleProblem;
+use crate::problems::countingProblem::CountingProblem;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::problems::sphereContinuousProblem::SphereContinuousNAProblem;
+use crate::problems::sphereDiscreteProblem::SphereDiscreteNAProblem;
+use crate::utils::{experimentContext::{Budget, ExperimentContext}, mathUtils};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub const TRACE_CSV_HEADER: &str = "R,Mutation Algorithm,Problem,Iteration,Generation,Score,Mutated Parameters,Toggled Connections,Step Sizes";
+pub const ARCHIVE_CSV_HEADER: &str = "R,Mutation Algorithm,Problem,Iteration,Behaviour X,Behaviour Y,Score";
+pub const PARETO_CSV_HEADER: &str = "R,Mutation Algorithm,Problem,Iteration,Score,Connections,Objectives";
+pub const CSV_HEADER: &str = "R,Mutation Algorithm,Problem,Input Dim,Hidden Dim,Output Dim,Iteration,Generation,Score,Solution,Seed,Evaluation Steps,Objectives,Evaluations,Restarts,Connections";
+
+#[derive(Data, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum AlgorithmEnum {
+    LocalOnePlusOneNA,
+    GaussianMutation,
+    // Gaussian mutation with its step size adapted by the 1/5th success rule
+    AdaptiveGaussian,
+    // AdaptiveGaussian with log-normal self-adaptation of per-parameter step sizes
+    LogNormalGaussian,
+    OnePlusOneNA,
+    // (1+1)NA and Local (1+1)NA with power-law distributed step sizes and numbers of mutated parameters
+    FastOnePlusOneNA,
+    FastLocalOnePlusOneNA,
+    SALocalMutation,
+    SAOnePlusOneNA,
+    CMAES,
+    // CMA-ES with candidates rounded to the 1/r grid
+    RoundedCMAES,
+    SimulatedAnnealing,
+    RandomLocalSearch
+}
+
+impl AlgorithmEnum {
+    // Every selectable algorithm, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, AlgorithmEnum)> {
+        vec![
+            ("Harmonic (1+1)NA", AlgorithmEnum::OnePlusOneNA),
+            ("Gaussian", AlgorithmEnum::GaussianMutation),
+            ("Gaussian 1/5th Rule", AlgorithmEnum::AdaptiveGaussian),
+            ("Gaussian 1/5th Rule Log-Normal", AlgorithmEnum::LogNormalGaussian),
+            ("Local (1+1)NA", AlgorithmEnum::LocalOnePlusOneNA),
+            ("Fast (1+1)NA", AlgorithmEnum::FastOnePlusOneNA),
+            ("Fast Local (1+1)NA", AlgorithmEnum::FastLocalOnePlusOneNA),
+            ("Self Adaptive (1+1)NA", AlgorithmEnum::SAOnePlusOneNA),
+            ("Self Adaptive Local", AlgorithmEnum::SALocalMutation),
+            ("CMA-ES", AlgorithmEnum::CMAES),
+            ("CMA-ES 1/R Grid", AlgorithmEnum::RoundedCMAES),
+            ("Simulated Annealing", AlgorithmEnum::SimulatedAnnealing),
+            ("Random Local Search", AlgorithmEnum::RandomLocalSearch),
+        ]
+    }
+
+    // Algorithms configured by the power-law exponent beta
+    pub fn usesPowerLaw(&self) -> bool {
+        matches!(self, AlgorithmEnum::FastOnePlusOneNA | AlgorithmEnum::FastLocalOnePlusOneNA)
+    }
+
+    // Algorithms that can switch connections off, configured by the connection toggle probability
+    pub fn togglesConnections(&self) -> bool {
+        matches!(self, AlgorithmEnum::OnePlusOneNA | AlgorithmEnum::LocalOnePlusOneNA | AlgorithmEnum::FastOnePlusOneNA | AlgorithmEnum::FastLocalOnePlusOneNA
+            | AlgorithmEnum::SAOnePlusOneNA | AlgorithmEnum::SALocalMutation | AlgorithmEnum::SimulatedAnnealing)
+    }
+
+    // Algorithms that evolve a population of their own, and so can't be used as the mutation step of a strategy or genetic algorithm
+    pub fn hasOwnPopulation(&self) -> bool {
+        matches!(self, AlgorithmEnum::CMAES | AlgorithmEnum::RoundedCMAES)
+    }
+
+    // Algorithms that decide on their own which candidates to keep, which the population runners would bypass by only using their variation
+    pub fn hasOwnAcceptance(&self) -> bool {
+        matches!(self, AlgorithmEnum::SimulatedAnnealing | AlgorithmEnum::RandomLocalSearch)
+    }
+
+    // Looks up an algorithm either by its display name or its variant name, ignoring case
+    pub fn fromName(name: &str) -> Option<AlgorithmEnum> {
+        AlgorithmEnum::all().into_iter()
+            .find(|(label, algorithm)| label.eq_ignore_ascii_case(name) || format!("{:?}", algorithm).eq_ignore_ascii_case(name))
+            .map(|(_, algorithm)| algorithm)
+    }
+}
+
+// Quality-diversity runners the mutation algorithm can be used in, searching for diverse behaviours instead of only the best score
+#[derive(Data, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum QualityDiversity {
+    MapElites,
+    NoveltySearch
+}
+
+impl QualityDiversity {
+    // Every quality-diversity runner, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, QualityDiversity)> {
+        vec![
+            ("MAP-Elites", QualityDiversity::MapElites),
+            ("Novelty Search", QualityDiversity::NoveltySearch),
+        ]
+    }
+
+    // Looks up a runner by its name, ignoring case. Dashes can stand for spaces, e.g. "novelty-search"
+    pub fn fromName(name: &str) -> Option<QualityDiversity> {
+        QualityDiversity::all().into_iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(name) || label.replace(' ', "-").eq_ignore_ascii_case(name))
+            .map(|(_, qualityDiversity)| qualityDiversity)
+    }
+}
+
+impl fmt::Display for QualityDiversity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = QualityDiversity::all().into_iter().find(|(_, qualityDiversity)| qualityDiversity == self).map_or("", |(label, _)| label);
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Data, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ProblemEnum {
+    SphereDiscrete(String),
+    SphereContinuous(String),
+    Cartpole(String),
+}
+
+impl ProblemEnum {
+    // Every selectable problem, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, ProblemEnum)> {
+        vec![
+            ("Sphere Continuous Quarter", ProblemEnum::SphereContinuous("Sphere Continuous Quarter".to_string())),
+            ("Sphere Continuous Half", ProblemEnum::SphereContinuous("Sphere Continuous Half".to_string())),
+            ("Sphere Continuous Two Quarters", ProblemEnum::SphereContinuous("Sphere Continuous Two Quarters".to_string())),
+            ("Sphere Continuous Local Optima", ProblemEnum::SphereContinuous("Sphere Continuous Local Optima".to_string())),
+            ("Sphere Discrete 2D Quarter", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Quarter".to_string())),
+            ("Sphere Discrete 2D Half", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Half".to_string())),
+            ("Sphere Discrete 2D Two Quarters", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Two Quarters".to_string())),
+            ("Sphere Discrete 2D Local Optima", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Local Optima".to_string())),
+            ("Sphere 3D Corner", ProblemEnum::SphereDiscrete("Sphere 3D Corner".to_string())),
+            ("Sphere 3D Half", ProblemEnum::SphereDiscrete("Sphere 3D Half".to_string())),
+            ("Sphere 3D Slice", ProblemEnum::SphereDiscrete("Sphere 3D Slice".to_string())),
+            ("Sphere 3D Two Slices", ProblemEnum::SphereDiscrete("Sphere 3D Two Slices".to_string())),
+            ("Sphere 4D Quarter", ProblemEnum::SphereDiscrete("Sphere 4D Quarter".to_string())),
+            ("Sphere 4D Half", ProblemEnum::SphereDiscrete("Sphere 4D Half".to_string())),
+            ("Sphere 4D Two Quarters", ProblemEnum::SphereDiscrete("Sphere 4D Two Quarters".to_string())),
+            ("Cartpole N Steps", ProblemEnum::Cartpole("Cartpole N Steps".to_string())),
+            ("Cartpole Discrete", ProblemEnum::Cartpole("Cartpole Discrete".to_string())),
+        ]
+    }
+
+    // Looks up a problem by its display name, ignoring case
+    pub fn fromName(name: &str) -> Option<ProblemEnum> {
+        ProblemEnum::all().into_iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(name))
+            .map(|(_, problem)| problem)
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ProblemEnum::SphereDiscrete(x) | ProblemEnum::SphereContinuous(x) | ProblemEnum::Cartpole(x) => x,
+        }
+    }
+}
+
+// Parses a comma separated list of hidden layer widths, e.g. "4,4,2". An empty list means no hidden layers
+pub fn parseHiddenDims(text: &str) -> Result<Vec<usize>, String> {
+    if text.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    text.split(',').map(|width| match width.trim().parse::<usize>() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(format!("Invalid hidden layer width: \"{}\"", width.trim()))
+    }).collect()
+}
+
+pub fn formatHiddenDims(hiddenDims: &[usize], separator: &str) -> String {
+    hiddenDims.iter().map(|width| width.to_string()).collect::<Vec<String>>().join(separator)
+}
+
+// Parses a comma separated list of activation names, e.g. "ReLU,Tanh". An empty list means Identity
+pub fn parseActivations(text: &str) -> Result<Vec<Activation>, String> {
+    if text.trim().is_empty() {
+        return Ok(vec![Activation::Identity]);
+    }
+    text.split(',').map(|name| Activation::fromName(name.trim()).ok_or(format!("Unknown activation: {}", name.trim()))).collect()
+}
+
+pub fn formatActivations(activations: &[Activation], separator: &str) -> String {
+    activations.iter().map(|activation| activation.to_string()).collect::<Vec<String>>().join(separator)
+}
+
+// One worker per available CPU core
+pub fn defaultWorkers() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// Receives the iteration, generation and best score so far whenever an iteration improves. Called from the worker threads
+pub type ProgressCallback = dyn Fn(u32, i32, f64) + Send + Sync;
+
+/*
+    Every parameter needed to run a batch of iterations of a single problem/algorithm combination.
+    Shared by the graphical user interface and the command line runner.
+ */
+#[derive(Clone)]
+pub struct ExperimentConfig {
+    pub problem: ProblemEnum,
+    pub algorithm: AlgorithmEnum,
+    // Population the algorithm's mutation is applied to. Any strategy other than (1+1) runs the algorithm inside a PopulationStrategy
+    pub strategy: Strategy,
+    // When set, the algorithm is used as the mutation step of a GeneticAlgorithm recombining with this operator. Requires the (1+1) strategy
+    pub crossover: Option<Crossover>,
+    pub populationSize: usize,
+    // Probability of an offspring of the genetic algorithm being recombined from two parents
+    pub crossoverRate: f64,
+    pub r: f64,
+    pub optimum: f64,
+    pub numberOfPoints: u32,
+    pub inputDim: usize,
+    // Widths of the hidden layers, from the input side. Empty for a network without hidden layers
+    pub hiddenDims: Vec<usize>,
+    pub outputDim: usize,
+    // Activation of every hidden layer, in order. A single activation is used by all of them
+    pub hiddenActivations: Vec<Activation>,
+    pub outputActivation: Activation,
+    pub bias: bool,
+    pub successAdaptation: f64,
+    pub failureAdaptation: f64,
+    // Power-law exponent of the fast algorithms. 1 gives the harmonic distribution of the (1+1)NA, larger values make large mutations rarer
+    pub beta: f64,
+    // Temperature schedule of simulated annealing. The rate is only used by the exponential schedule
+    pub coolingSchedule: Cooling,
+    pub initialTemperature: f64,
+    pub coolingRate: f64,
+    // Probability that the (1+1)NA family switches a drawn connection on or off instead of changing its weight
+    pub connectionToggleProbability: f64,
+    // Runs the algorithm in a quality-diversity runner, None to run it on its own. Novelty search keeps `populationSize` parents
+    pub qualityDiversity: Option<QualityDiversity>,
+    // Number of cells along each behaviour dimension of the archive
+    pub archiveCells: usize,
+    // Runs the algorithm in NSGA-II with `populationSize` parents, trading the problem's objectives off against the number of connections
+    pub multiObjective: bool,
+    // Restarts the algorithm when it stalls, None to never restart
+    pub restart: Option<RestartMode>,
+    // Generations without improvement after which random local search or the restart strategy restarts
+    pub restartPatience: u64,
+    pub iterations: u32,
+    // Limits of every iteration. When none is set, iterations are limited to 100 * R * log2(R) generations
+    pub maxGenerations: Option<u64>,
+    pub maxEvaluations: Option<u64>,
+    // Wall-clock limit, in seconds
+    pub maxTime: Option<f64>,
+    // Number of the first iteration ran. Together with the seed, allows replaying a single iteration
+    pub firstIteration: u32,
+    // Master seed every random source of every iteration is derived from
+    pub seed: u64,
+    // Number of threads iterations are distributed across
+    pub workers: usize,
+    // Raised to stop the experiment early. Iterations interrupted by it are not reported
+    pub cancelFlag: Option<Arc<AtomicBool>>,
+    pub onProgress: Option<Arc<ProgressCallback>>,
+    pub outputFile: Option<String>,
+    // File every accepted mutation is recorded to, as JSON Lines if it ends in .jsonl and CSV otherwise
+    pub traceFile: Option<String>,
+    // File the behaviour archive of every iteration is written to, one row per filled cell
+    pub archiveFile: Option<String>,
+    // File the final Pareto front of every NSGA-II iteration is written to, one row per member
+    pub paretoFile: Option<String>,
+    // Directory the best network of every iteration is saved to, in `networkFormat`
+    pub networkDirectory: Option<String>,
+    pub networkFormat: NetworkFormat,
+    // Network every iteration starts from instead of a random one. Set with `withInitialNetwork`
+    pub initialNetwork: Option<NANN>
+}
+
+impl ExperimentConfig {
+    pub fn default() -> ExperimentConfig {
+        ExperimentConfig {
+            problem: ProblemEnum::SphereContinuous("Sphere Continuous Quarter".to_string()),
+            algorithm: AlgorithmEnum::OnePlusOneNA,
+            strategy: Strategy::onePlusOne(),
+            crossover: None,
+            populationSize: 20,
+            crossoverRate: 0.9,
+            r: 120.0,
+            optimum: 1.0,
+            numberOfPoints: 1000,
+            inputDim: 2,
+            hiddenDims: vec![1],
+            outputDim: 2,
+            hiddenActivations: vec![Activation::Identity],
+            outputActivation: Activation::Identity,
+            bias: true,
+            successAdaptation: 1.7,
+            failureAdaptation: 0.9,
+            beta: 1.5,
+            coolingSchedule: Cooling::Exponential,
+            initialTemperature: 0.05,
+            coolingRate: 0.999,
+            connectionToggleProbability: 0.0,
+            qualityDiversity: None,
+            archiveCells: 20,
+            multiObjective: false,
+            restart: None,
+            restartPatience: 1000,
+            iterations: 1,
+            maxGenerations: None,
+            maxEvaluations: None,
+            maxTime: None,
+            firstIteration: 1,
+            seed: rand::thread_rng().gen(),
+            workers: defaultWorkers(),
+            cancelFlag: None,
+            onProgress: None,
+            outputFile: None,
+            traceFile: None,
+            archiveFile: None,
+            paretoFile: None,
+            networkDirectory: None,
+            networkFormat: NetworkFormat::Binary,
+            initialNetwork: None
+        }
+    }
+
+    // Warm starts every iteration from `nn`, taking the network dimensions, activations and bias flag from it
+    pub fn withInitialNetwork(mut self, nn: NANN) -> ExperimentConfig {
+        let layerSizes = nn.layerSizes();
+        self.inputDim = layerSizes[0].0;
+        self.hiddenDims = layerSizes[..layerSizes.len() - 1].iter().map(|(_, outputSize)| *outputSize).collect();
+        self.outputDim = layerSizes[layerSizes.len() - 1].1;
+        self.hiddenActivations = nn.layers[..nn.layers.len() - 1].iter().map(|layer| layer.activation).collect();
+        self.outputActivation = nn.layers[nn.layers.len() - 1].activation;
+        self.bias = nn.isUsingBias();
+        self.initialNetwork = Some(nn);
+        self
+    }
+
+    pub fn isCancelled(&self) -> bool {
+        self.cancelFlag.as_ref().map_or(false, |flag| flag.load(Ordering::Relaxed))
+    }
+
+    pub fn iterationRange(&self) -> std::ops::Range<u32> {
+        self.firstIteration..self.firstIteration + self.iterations
+    }
+
+    // [(inputs, first hidden width), ..., (last hidden width, outputs)]
+    pub fn layerSizes(&self) -> Vec<(usize, usize)> {
+        let mut dims = vec![self.inputDim];
+        dims.extend(&self.hiddenDims);
+        dims.push(self.outputDim);
+        dims.windows(2).map(|w| (w[0], w[1])).collect()
+    }
+
+    // Activation of every layer: the hidden activations, then the output activation for the last layer
+    pub fn activations(&self) -> Vec<Activation> {
+        let mut activations = match self.hiddenActivations.as_slice() {
+            [activation] => vec![*activation; self.hiddenDims.len()],
+            activations => activations.to_vec()
+        };
+        activations.push(self.outputActivation);
+        activations
+    }
+
+    pub fn context(&self) -> ExperimentContext {
+        ExperimentContext::new(self.r, self.optimum)
+            .withCancelFlag(self.cancelFlag.clone())
+            .withBudget(self.budget())
+    }
+
+    pub fn budget(&self) -> Budget {
+        Budget {
+            maxGenerations: self.maxGenerations,
+            maxEvaluations: self.maxEvaluations,
+            maxTime: self.maxTime.map(Duration::from_secs_f64)
+        }
+    }
+
+    // Checks the parameters that can't be represented in a runnable experiment
+    pub fn validate(&self) -> Result<(), String> {
+        NANN::validateLayerSizes(&self.layerSizes())?;
+        if self.hiddenActivations.len() != 1 && self.hiddenActivations.len() != self.hiddenDims.len() {
+            return Err(format!("Got {} hidden activations for {} hidden layers: give one per hidden layer, or a single one for all of them", self.hiddenActivations.len(), self.hiddenDims.len()));
+        }
+        if self.algorithm.usesPowerLaw() && !(self.beta >= 0.0 && self.beta.is_finite()) {
+            return Err(format!("Beta must be a non-negative number: {}", self.beta));
+        }
+        if self.algorithm == AlgorithmEnum::SimulatedAnnealing {
+            if !(self.initialTemperature > 0.0) {
+                return Err(format!("The initial temperature must be positive: {}", self.initialTemperature));
+            }
+            if !(self.coolingRate > 0.0 && self.coolingRate <= 1.0) {
+                return Err(format!("The cooling rate must be in (0, 1]: {}", self.coolingRate));
+            }
+            // Without a generation limit, the linear schedule wouldn't know when the run ends. The default limit only applies when no budget is set
+            if self.coolingSchedule == Cooling::Linear && self.maxGenerations.is_none() && (self.maxEvaluations.is_some() || self.maxTime.is_some()) {
+                return Err("Linear cooling needs a generation limit when iterations are limited by evaluations or time".to_string());
+            }
+        }
+        if self.algorithm.togglesConnections() && !(0.0..=1.0).contains(&self.connectionToggleProbability) {
+            return Err(format!("The connection toggle probability must be between 0 and 1: {}", self.connectionToggleProbability));
+        }
+        if (self.algorithm == AlgorithmEnum::RandomLocalSearch || self.restart.is_some()) && self.restartPatience == 0 {
+            return Err("The restart patience must be at least 1 generation".to_string());
+        }
+        if self.restart.is_some() && (self.algorithm.hasOwnPopulation() || self.algorithm == AlgorithmEnum::RandomLocalSearch) {
+            return Err(format!("{} already restarts on its own", AlgorithmEnum::all().into_iter().find(|(_, a)| *a == self.algorithm).unwrap().0));
+        }
+        if self.algorithm.hasOwnPopulation() && (self.crossover.is_some() || !self.strategy.isOnePlusOne()) {
+            return Err(format!("{} can't be used in a strategy or genetic algorithm", AlgorithmEnum::all().into_iter().find(|(_, a)| *a == self.algorithm).unwrap().0));
+        }
+        if self.algorithm.hasOwnAcceptance() && (self.crossover.is_some() || !self.strategy.isOnePlusOne() || self.qualityDiversity.is_some() || self.multiObjective) {
+            return Err(format!("{} can't be used in a strategy, genetic algorithm, quality-diversity runner or NSGA-II", AlgorithmEnum::all().into_iter().find(|(_, a)| *a == self.algorithm).unwrap().0));
+        }
+        if let Some(qualityDiversity) = self.qualityDiversity {
+            if self.algorithm.hasOwnPopulation() || self.crossover.is_some() || !self.strategy.isOnePlusOne() || self.restart.is_some() {
+                return Err(format!("{} can't be combined with a population-based algorithm, strategy, genetic algorithm or restarts", qualityDiversity));
+            }
+            if self.archiveCells == 0 {
+                return Err("The archive needs at least 1 cell per dimension".to_string());
+            }
+            if qualityDiversity == QualityDiversity::NoveltySearch && self.populationSize < 2 {
+                return Err("Novelty search needs a population of at least 2".to_string());
+            }
+        }
+        if self.multiObjective {
+            if self.algorithm.hasOwnPopulation() || self.crossover.is_some() || !self.strategy.isOnePlusOne() || self.restart.is_some() || self.qualityDiversity.is_some() {
+                return Err("NSGA-II can't be combined with a population-based algorithm, strategy, genetic algorithm, quality-diversity runner or restarts".to_string());
+            }
+            if self.populationSize < 2 {
+                return Err("NSGA-II needs a population of at least 2".to_string());
+            }
+            // Otherwise every network keeps the same number of connections, and the second objective never changes
+            if !self.algorithm.togglesConnections() || !(self.connectionToggleProbability > 0.0) {
+                return Err("NSGA-II needs an algorithm that toggles connections, with a connection toggle probability above 0".to_string());
+            }
+        }
+        if self.crossover.is_some() {
+            if !self.strategy.isOnePlusOne() {
+                return Err(format!("A genetic algorithm can't be combined with the {} strategy", self.strategy));
+            }
+            if self.populationSize < 2 {
+                return Err("A genetic algorithm needs a population of at least 2".to_string());
+            }
+            if !(0.0..=1.0).contains(&self.crossoverRate) {
+                return Err(format!("The crossover rate must be between 0 and 1: {}", self.crossoverRate));
+            }
+        }
+        Ok(())
+    }
+
+    // Human readable summary of the parameters, one line per group
+    pub fn describe(&self) -> Vec<String> {
+        let mut problemString = "Problem: ".to_string();
+        problemString.push_str(self.problem.name());
+        if let ProblemEnum::SphereDiscrete(_) = self.problem {
+            problemString.push_str(", Number of Points: ");
+            problemString.push_str(&self.numberOfPoints.to_string());
+        }
+        let mut algorithmString = "Mutation Algorithm: ".to_string();
+        match &self.algorithm {
+            AlgorithmEnum::SALocalMutation => {
+                algorithmString.push_str("Self Adaptive Local");
+                algorithmString.push_str(", Success Multiplier: ");
+                algorithmString.push_str(&self.successAdaptation.to_string());
+                algorithmString.push_str(", Failure Multiplier: ");
+                algorithmString.push_str(&self.failureAdaptation.to_string());
+            },
+            AlgorithmEnum::SAOnePlusOneNA => {
+                algorithmString.push_str("Self Adaptive (1+1)NA");
+            },
+            AlgorithmEnum::GaussianMutation => {
+                algorithmString.push_str("Gaussian");
+            },
+            AlgorithmEnum::AdaptiveGaussian => {
+                algorithmString.push_str("Gaussian, 1/5th success rule");
+            },
+            AlgorithmEnum::LogNormalGaussian => {
+                algorithmString.push_str("Gaussian, 1/5th success rule and log-normal self-adaptation");
+            },
+            AlgorithmEnum::OnePlusOneNA => {
+                algorithmString.push_str("(1+1)NA");
+            },
+            AlgorithmEnum::LocalOnePlusOneNA => {
+                algorithmString.push_str("Local (1+1)NA");
+            },
+            AlgorithmEnum::FastOnePlusOneNA => {
+                algorithmString.push_str("Fast (1+1)NA, Beta: ");
+                algorithmString.push_str(&self.beta.to_string());
+            },
+            AlgorithmEnum::FastLocalOnePlusOneNA => {
+                algorithmString.push_str("Fast Local (1+1)NA, Beta: ");
+                algorithmString.push_str(&self.beta.to_string());
+            },
+            AlgorithmEnum::CMAES => {
+                algorithmString.push_str("CMA-ES");
+            },
+            AlgorithmEnum::RoundedCMAES => {
+                algorithmString.push_str("CMA-ES, rounded to the 1/R grid");
+            },
+            AlgorithmEnum::SimulatedAnnealing => {
+                algorithmString.push_str(&format!("Simulated Annealing, {} Cooling, Initial Temperature: {}", self.coolingSchedule, self.initialTemperature));
+                if self.coolingSchedule == Cooling::Exponential {
+                    algorithmString.push_str(&format!(", Cooling Rate: {}", self.coolingRate));
+                }
+            },
+            AlgorithmEnum::RandomLocalSearch => {
+                algorithmString.push_str("Random Local Search, Restart Patience: ");
+                algorithmString.push_str(&self.restartPatience.to_string());
+            }
+        }
+        if self.algorithm.togglesConnections() && self.connectionToggleProbability > 0.0 {
+            algorithmString.push_str(&format!(", Connection Toggle Probability: {}", self.connectionToggleProbability));
+        }
+        if !self.strategy.isOnePlusOne() {
+            algorithmString.push_str(", Strategy: ");
+            algorithmString.push_str(&self.strategy.to_string());
+        }
+        if let Some(crossover) = self.crossover {
+            algorithmString.push_str(&format!(", Genetic Algorithm: {} Crossover, Population: {}, Crossover Rate: {}", crossover, self.populationSize, self.crossoverRate));
+        }
+        match self.qualityDiversity {
+            Some(QualityDiversity::MapElites) => algorithmString.push_str(&format!(", MAP-Elites, Archive: {0}x{0} Cells", self.archiveCells)),
+            Some(QualityDiversity::NoveltySearch) => algorithmString.push_str(&format!(", Novelty Search, Population: {1}, Archive: {0}x{0} Cells", self.archiveCells, self.populationSize)),
+            None => {}
+        }
+        if self.multiObjective {
+            algorithmString.push_str(&format!(", NSGA-II, Population: {}", self.populationSize));
+        }
+        if let Some(restart) = self.restart {
+            algorithmString.push_str(&format!(", Restarts: {}, Restart Patience: {}", restart, self.restartPatience));
+        }
+        let networkString = format!("Inputs: {}, Hidden Layers: [{}] ({}), Outputs: {} ({}), Resolution: {}, Optimum: {}\n",
+            self.inputDim,
+            formatHiddenDims(&self.hiddenDims, ","),
+            formatActivations(&self.hiddenActivations, ","),
+            self.outputDim,
+            self.outputActivation,
+            self.r,
+            self.optimum
+        );
+        let budget = self.context().budget;
+        let mut limits = vec![];
+        if let Some(max) = budget.maxGenerations { limits.push(format!("{} generations", max)); }
+        if let Some(max) = budget.maxEvaluations { limits.push(format!("{} evaluations", max)); }
+        if let Some(max) = budget.maxTime { limits.push(format!("{}s", max.as_secs_f64())); }
+        vec![
+            format!("Running {} experiments with parameters (seed {}):", self.iterations, self.seed),
+            problemString,
+            algorithmString,
+            format!("Budget per iteration: {}", limits.join(", ")),
+            networkString
+        ]
+    }
+}
+
+pub struct IterationResult {
+    pub iteration: u32,
+    pub generation: i32,
+    // Evaluation of the best network of the iteration
+    pub evaluation: Evaluation,
+    // Number of times the problem was evaluated during the iteration, including the mutation algorithm's evaluations
+    pub evaluations: u64,
+    // Number of times the mutation algorithm restarted from a new network
+    pub restarts: u32,
+    pub problemName: String,
+    pub algorithmName: String,
+    // Every accepted mutation, only recorded when the experiment has a trace file
+    pub trace: Vec<TraceRecord>,
+    // Final behaviour archive, for the quality-diversity runners
+    pub archive: Vec<ArchiveCell>,
+    // Final Pareto front, for NSGA-II
+    pub paretoFront: Vec<ParetoPoint>,
+    // Network with the best score of the iteration
+    pub network: NANN
+}
+
+pub struct TraceRecord {
+    pub generation: i32,
+    pub score: f64,
+    pub mutatedParameters: usize,
+    pub toggledConnections: usize,
+    pub stepSizes: Vec<f64>
+}
+
+// Forwards an iteration's progress to the experiment's callback and records its trace
+struct IterationObserver<'a> {
+    config: &'a ExperimentConfig,
+    iteration: u32,
+    trace: Vec<TraceRecord>,
+    archive: Vec<ArchiveCell>,
+    paretoFront: Vec<ParetoPoint>
+}
+
+impl RunObserver for IterationObserver<'_> {
+    fn onImprovement(&mut self, generation: i32, bestScore: f64) {
+        if let Some(onProgress) = &self.config.onProgress {
+            onProgress(self.iteration, generation, bestScore);
+        }
+    }
+
+    fn onFinished(&mut self, mutationAlgorithm: &dyn MutationAlgorithm) {
+        self.archive = mutationAlgorithm.archive();
+        self.paretoFront = mutationAlgorithm.paretoFront();
+    }
+
+    fn onAcceptedMutation(&mut self, generation: i32, mutation: &MutationResult) {
+        if self.config.traceFile.is_some() {
+            self.trace.push(TraceRecord {
+                generation,
+                score: mutation.evaluation.score,
+                mutatedParameters: mutation.mutatedParameters,
+                toggledConnections: mutation.toggledConnections,
+                stepSizes: mutation.stepSizes.clone()
+            });
+        }
+    }
+}
+
+impl IterationResult {
+    // Appended to the summary of the iteration, empty if the algorithm never restarted
+    pub fn restartsSummary(&self) -> String {
+        match self.restarts {
+            0 => "".to_string(),
+            1 => " after 1 restart".to_string(),
+            restarts => format!(" after {} restarts", restarts)
+        }
+    }
+
+    // Row of the results file, matching the columns in CSV_HEADER
+    pub fn toCsvRow(&self, config: &ExperimentConfig) -> String {
+        let exportSolutionString: String = self.evaluation.solution().map(|x| x.to_string()).into_raw_vec().join(";");
+        format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            config.r, csvField(&self.algorithmName), self.problemName, config.inputDim, formatHiddenDims(&config.hiddenDims, ";"), config.outputDim, self.iteration, self.generation, self.evaluation.score, exportSolutionString, config.seed,
+            self.evaluation.steps, self.evaluation.objectivesString(), self.evaluations, self.restarts, self.network.connectionCount()
+        )
+    }
+}
+
+pub fn buildProblem(config: &ExperimentConfig, seed: u64) -> Box<dyn NAProblem> {
+    match &config.problem {
+        ProblemEnum::SphereDiscrete(x) => {
+            match x.as_str() {
+                "Sphere Discrete 2D Quarter" => SphereDiscreteNAProblem::newQuarter(config.numberOfPoints, seed),
+                "Sphere Discrete 2D Half" => SphereDiscreteNAProblem::newHalf(config.numberOfPoints, seed),
+                "Sphere Discrete 2D Two Quarters" => SphereDiscreteNAProblem::newTwoQuarters(config.numberOfPoints, seed),
+                "Sphere Discrete 2D Local Optima" => SphereDiscreteNAProblem::newLocalOpt(config.numberOfPoints, seed),
+                "Sphere 3D Corner" => SphereDiscreteNAProblem::newCorner3D(config.numberOfPoints, seed),
+                "Sphere 3D Half" => SphereDiscreteNAProblem::newHalf3D(config.numberOfPoints, seed),
+                "Sphere 3D Slice" => SphereDiscreteNAProblem::newSlice3D(config.numberOfPoints, seed),
+                "Sphere 3D Two Slices" => SphereDiscreteNAProblem::newTwoSlices3D(config.numberOfPoints, seed),
+                "Sphere 4D Quarter" => SphereDiscreteNAProblem::newQuarter4D(config.numberOfPoints, seed),
+                "Sphere 4D Half" => SphereDiscreteNAProblem::newHalf4D(config.numberOfPoints, seed),
+                "Sphere 4D Two Quarters" => SphereDiscreteNAProblem::newTwoQuarters4D(config.numberOfPoints, seed),
+                _ => SphereDiscreteNAProblem::newQuarter(config.numberOfPoints, seed),
+            }
+        },
+        ProblemEnum::SphereContinuous(x) => {
+            match x.as_str() {
+                "Sphere Continuous Quarter" => SphereContinuousNAProblem::newQuarter(),
+                "Sphere Continuous Half" => SphereContinuousNAProblem::newHalf(),
+                "Sphere Continuous Two Quarters" => SphereContinuousNAProblem::newTwoQuarters(),
+                "Sphere Continuous Local Optima" => SphereContinuousNAProblem::newLocalOpt(),
+                _ => SphereContinuousNAProblem::newQuarter()
+            }
+        },
+        ProblemEnum::Cartpole(x) => {
+            match x.as_str() {
+                "Cartpole N Steps" => CartpoleProblem::newNSteps(seed),
+                "Cartpole Discrete" => CartpoleProblem::newDiscretizedContinuous(seed),
+                _ => CartpoleProblem::newNSteps(seed),
+            }
+        },
+    }
+}
+
+pub fn buildAlgorithm(config: &ExperimentConfig, nn: &NANN, problem: Box<dyn NAProblem>, seed: u64) -> Box<dyn MutationAlgorithm> {
+    let operator = match &config.algorithm {
+        AlgorithmEnum::SALocalMutation => {
+            SALocalMutation::new(nn, problem, config.r, config.successAdaptation, config.failureAdaptation, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::SAOnePlusOneNA => {
+            SAOnePlusOneNA::new(nn, problem, config.r, config.successAdaptation, config.failureAdaptation, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::GaussianMutation => {
+            GaussianMutation::new(problem, seed)
+        },
+        AlgorithmEnum::AdaptiveGaussian => {
+            AdaptiveGaussianMutation::new(nn, problem, false, seed)
+        },
+        AlgorithmEnum::LogNormalGaussian => {
+            AdaptiveGaussianMutation::new(nn, problem, true, seed)
+        },
+        AlgorithmEnum::OnePlusOneNA => {
+            OnePlusOneNA::new(nn, problem, config.r, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::LocalOnePlusOneNA => {
+            LocalOnePlusOneNA::new(nn, problem, config.r, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::FastOnePlusOneNA => {
+            FastOnePlusOneNA::new(nn, problem, config.r, config.beta, false, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::FastLocalOnePlusOneNA => {
+            FastOnePlusOneNA::new(nn, problem, config.r, config.beta, true, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::CMAES => {
+            CMAES::new(nn, problem, config.r, false, seed)
+        },
+        AlgorithmEnum::RoundedCMAES => {
+            CMAES::new(nn, problem, config.r, true, seed)
+        },
+        AlgorithmEnum::SimulatedAnnealing => {
+            let schedule = CoolingSchedule {
+                cooling: config.coolingSchedule,
+                initialTemperature: config.initialTemperature,
+                rate: config.coolingRate
+            };
+            SimulatedAnnealing::new(nn, problem, config.r, schedule, config.connectionToggleProbability, seed)
+        },
+        AlgorithmEnum::RandomLocalSearch => {
+            RandomLocalSearch::new(problem, config.r, config.restartPatience, seed)
+        }
+    };
+    // Populations and restarts get their own random sources so the operator's stays the same as when it runs alone
+    let algorithm = if let Some(qualityDiversity) = config.qualityDiversity {
+        match qualityDiversity {
+            QualityDiversity::MapElites => MapElites::new(operator, config.archiveCells, mathUtils::deriveSeed(seed, 0)),
+            QualityDiversity::NoveltySearch => NoveltySearch::new(operator, config.populationSize, config.archiveCells, mathUtils::deriveSeed(seed, 0))
+        }
+    } else if config.multiObjective {
+        NSGAII::new(operator, config.populationSize, mathUtils::deriveSeed(seed, 0))
+    } else if let Some(crossover) = config.crossover {
+        GeneticAlgorithm::new(operator, crossover, config.populationSize, config.crossoverRate, mathUtils::deriveSeed(seed, 0))
+    } else if !config.strategy.isOnePlusOne() {
+        PopulationStrategy::new(operator, config.strategy, mathUtils::deriveSeed(seed, 0))
+    } else {
+        operator
+    };
+    match config.restart {
+        Some(mode) => RestartStrategy::new(algorithm, mode, config.r, config.restartPatience, mathUtils::deriveSeed(seed, 1)),
+        None => algorithm
+    }
+}
+
+// Quotes a CSV field containing commas, such as the name of a (μ,λ) strategy
+pub fn csvField(value: &str) -> String {
+    if value.contains(',') { format!("\"{}\"", value) } else { value.to_string() }
+}
+
+// Creates (or truncates) a results file containing only the CSV header
+pub fn createResultsFile(outputFile: &str) {
+    let mut resultFile = File::create(outputFile).expect("Unable to created final result file");
+    writeln!(resultFile, "{}", CSV_HEADER).expect("Failed writing result file");
+}
+
+pub fn appendResult(outputFile: &str, result: &IterationResult, config: &ExperimentConfig) {
+    let mut resultFile = OpenOptions::new().append(true).open(outputFile).unwrap();
+    writeln!(resultFile, "{}", result.toCsvRow(config)).expect("Failed writing result file");
+}
+
+// Creates the directory networks are saved to, if the experiment saves them
+pub fn createNetworkDirectory(config: &ExperimentConfig) {
+    if let Some(directory) = &config.networkDirectory {
+        fs::create_dir_all(directory).expect("Unable to create network directory");
+    }
+}
+
+// Saves the iteration's best network, named after the experiment's parameters and the iteration
+pub fn saveNetwork(result: &IterationResult, config: &ExperimentConfig) {
+    if let Some(directory) = &config.networkDirectory {
+        let fileName = format!("{}_{}_R{}_H{}_iteration{}.{}",
+            result.problemName, result.algorithmName, config.r, formatHiddenDims(&config.hiddenDims, "-"), result.iteration, config.networkFormat.extension()
+        ).replace(' ', "_");
+        let path = std::path::Path::new(directory).join(fileName);
+        result.network.save(&path.to_string_lossy(), &NetworkMetadata { resolution: config.r, optimum: config.optimum }).expect("Failed writing network file");
+    }
+}
+
+// Initial network, problem instance and algorithm seed of an iteration. The same seed and iteration always give the same problem instance
+pub fn setupIteration(config: &ExperimentConfig, iteration: u32) -> (NANN, Box<dyn NAProblem>, u64) {
+    // Each iteration gets its own seed, from which the network, problem and algorithm seeds are drawn
+    let mut seeder = StdRng::seed_from_u64(mathUtils::deriveSeed(config.seed, iteration as u64));
+    let randomNN = NANN::new(config.layerSizes(), &config.activations(), config.r, config.bias, &mut seeder);
+    // A warm start replaces the random network, which is still drawn so the problem and algorithm seeds stay the same
+    let nn = config.initialNetwork.clone().unwrap_or(randomNN);
+    let problem = buildProblem(config, seeder.gen());
+    (nn, problem, seeder.gen())
+}
+
+// Runs a single iteration of the experiment described by `config`, from network initialization to the final generation.
+// Returns None if the experiment was cancelled before the iteration finished.
+pub fn runIteration(config: &ExperimentConfig, iteration: u32) -> Option<IterationResult> {
+    let (nn, problem, algorithmSeed) = setupIteration(config, iteration);
+    let (problem, evaluations) = CountingProblem::wrap(problem);
+    let mutationAlgorithm = buildAlgorithm(config, &nn, problem, algorithmSeed);
+
+    let problemName = mutationAlgorithm.getProblem().to_string();
+    let algorithmName = mutationAlgorithm.to_string();
+    let context = config.context();
+    let mut observer = IterationObserver {
+        config,
+        iteration,
+        trace: vec![],
+        archive: vec![],
+        paretoFront: vec![]
+    };
+    let (generation, _, evaluation, network, restarts) = ann::run(
+        nn,
+        mutationAlgorithm,
+        &context,
+        &evaluations,
+        &mut observer
+    );
+    if context.isCancelled() {
+        return None;
+    }
+    Some(IterationResult {
+        iteration,
+        generation,
+        evaluation,
+        evaluations: evaluations.get(),
+        restarts,
+        problemName,
+        algorithmName,
+        trace: observer.trace,
+        archive: observer.archive,
+        paretoFront: observer.paretoFront,
+        network
+    })
+}
+
+// Creates (or truncates) a trace file. CSV traces start with their header
+pub fn createTraceFile(traceFile: &str) {
+    let mut file = File::create(traceFile).expect("Unable to create trace file");
+    if !isJsonLines(traceFile) {
+        writeln!(file, "{}", TRACE_CSV_HEADER).expect("Failed writing trace file");
+    }
+}
+
+pub fn appendTrace(traceFile: &str, result: &IterationResult, config: &ExperimentConfig) {
+    let mut file = BufWriter::new(OpenOptions::new().append(true).open(traceFile).unwrap());
+    for record in &result.trace {
+        if isJsonLines(traceFile) {
+            let line = serde_json::json!({
+                "r": config.r,
+                "algorithm": result.algorithmName,
+                "problem": result.problemName,
+                "iteration": result.iteration,
+                "generation": record.generation,
+                "score": record.score,
+                "mutatedParameters": record.mutatedParameters,
+                "toggledConnections": record.toggledConnections,
+                "stepSizes": record.stepSizes
+            });
+            writeln!(file, "{}", line).expect("Failed writing trace file");
+        } else {
+            let stepSizes: Vec<String> = record.stepSizes.iter().map(|s| s.to_string()).collect();
+            writeln!(file, "{},{},{},{},{},{},{},{},{}",
+                config.r, csvField(&result.algorithmName), result.problemName, result.iteration, record.generation, record.score, record.mutatedParameters, record.toggledConnections, stepSizes.join(";")
+            ).expect("Failed writing trace file");
+        }
+    }
+}
+
+// Creates (or truncates) an archive file containing only the CSV header
+pub fn createArchiveFile(archiveFile: &str) {
+    let mut file = File::create(archiveFile).expect("Unable to create archive file");
+    writeln!(file, "{}", ARCHIVE_CSV_HEADER).expect("Failed writing archive file");
+}
+
+// Appends one row per filled cell of the iteration's archive, with the cell's coordinates in the behaviour grid
+pub fn appendArchive(archiveFile: &str, result: &IterationResult, config: &ExperimentConfig) {
+    let mut file = BufWriter::new(OpenOptions::new().append(true).open(archiveFile).unwrap());
+    for cell in &result.archive {
+        let coordinates: Vec<String> = cell.cell.iter().map(|c| c.to_string()).collect();
+        writeln!(file, "{},{},{},{},{},{}",
+            config.r, csvField(&result.algorithmName), result.problemName, result.iteration, coordinates.join(","), cell.score
+        ).expect("Failed writing archive file");
+    }
+}
+
+// Creates (or truncates) a Pareto front file containing only the CSV header
+pub fn createParetoFile(paretoFile: &str) {
+    let mut file = File::create(paretoFile).expect("Unable to create Pareto front file");
+    writeln!(file, "{}", PARETO_CSV_HEADER).expect("Failed writing Pareto front file");
+}
+
+// Appends one row per member of the iteration's Pareto front, with its objectives in the form "name=value", separated by ';'
+pub fn appendParetoFront(paretoFile: &str, result: &IterationResult, config: &ExperimentConfig) {
+    let mut file = BufWriter::new(OpenOptions::new().append(true).open(paretoFile).unwrap());
+    for point in &result.paretoFront {
+        let objectives: Vec<String> = point.objectives.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        writeln!(file, "{},{},{},{},{},{},{}",
+            config.r, csvField(&result.algorithmName), result.problemName, result.iteration, point.score, point.connections, objectives.join(";")
+        ).expect("Failed writing Pareto front file");
+    }
+}
+
+fn isJsonLines(path: &str) -> bool {
+    path.to_lowercase().ends_with(".jsonl")
+}
+
+/*
+    Runs every job, an (experiment, iteration) pair, on a pool of `workers` threads.
+    `onResult` is called on the calling thread with the job's index, always in the same order as `jobs`,
+    so the results are written deterministically no matter which thread finishes first.
+    Jobs whose experiment is cancelled are skipped, and only the iterations that completed are reported.
+ */
+pub fn runJobs(jobs: &[(&ExperimentConfig, u32)], workers: usize, mut onResult: impl FnMut(usize, IterationResult)) {
+    let nextJob = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<(usize, Option<IterationResult>)>();
+    thread::scope(|scope| {
+        for _ in 0..workers.max(1).min(jobs.len()) {
+            let sender = sender.clone();
+            let nextJob = &nextJob;
+            scope.spawn(move || {
+                loop {
+                    let index = nextJob.fetch_add(1, Ordering::Relaxed);
+                    if index >= jobs.len() {
+                        break;
+                    }
+                    let (config, iteration) = jobs[index];
+                    let result = if config.isCancelled() { None } else { runIteration(config, iteration) };
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Results that finished ahead of their turn wait here until every job before them is done
+        let mut pending: BTreeMap<usize, Option<IterationResult>> = BTreeMap::new();
+        let mut nextResult = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&nextResult) {
+                if let Some(result) = result {
+                    onResult(nextResult, result);
+                }
+                nextResult += 1;
+            }
+        }
+    });
+}
+
+/*
+    Runs every iteration of the experiment described by `config`, calling `onIteration` as each one finishes, in iteration order.
+    If an output file is set, it is (re)created with the CSV header and one row is appended per iteration.
+ */
+pub fn runExperiments(config: &ExperimentConfig, mut onIteration: impl FnMut(&IterationResult)) {
+    if let Some(outputFile) = &config.outputFile {
+        createResultsFile(outputFile);
+    }
+    if let Some(traceFile) = &config.traceFile {
+        createTraceFile(traceFile);
+    }
+    if let Some(archiveFile) = &config.archiveFile {
+        createArchiveFile(archiveFile);
+    }
+    if let Some(paretoFile) = &config.paretoFile {
+        createParetoFile(paretoFile);
+    }
+    createNetworkDirectory(config);
+    let jobs: Vec<(&ExperimentConfig, u32)> = config.iterationRange().map(|i| (config, i)).collect();
+    runJobs(&jobs, config.workers, |_, result| {
+        if let Some(outputFile) = &config.outputFile {
+            appendResult(outputFile, &result, config);
+        }
+        if let Some(traceFile) = &config.traceFile {
+            appendTrace(traceFile, &result, config);
+        }
+        if let Some(archiveFile) = &config.archiveFile {
+            appendArchive(archiveFile, &result, config);
+        }
+        if let Some(paretoFile) = &config.paretoFile {
+            appendParetoFront(paretoFile, &result, config);
+        }
+        saveNetwork(&result, config);
+        onIteration(&result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ownAcceptanceIsRejectedInPopulationRunners() {
+        for algorithm in [AlgorithmEnum::SimulatedAnnealing, AlgorithmEnum::RandomLocalSearch] {
+            let mut config = ExperimentConfig::default();
+            config.algorithm = algorithm;
+            assert!(config.validate().is_ok());
+
+            let mut strategy = config.clone();
+            strategy.strategy = Strategy::fromName("(5+10)").unwrap();
+            assert!(strategy.validate().is_err());
+
+            let mut geneticAlgorithm = config.clone();
+            geneticAlgorithm.crossover = Some(Crossover::Uniform);
+            assert!(geneticAlgorithm.validate().is_err());
+
+            for qualityDiversity in [QualityDiversity::MapElites, QualityDiversity::NoveltySearch] {
+                let mut archive = config.clone();
+                archive.qualityDiversity = Some(qualityDiversity);
+                assert!(archive.validate().is_err());
+            }
+
+            let mut multiObjective = config.clone();
+            multiObjective.multiObjective = true;
+            assert!(multiObjective.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn multiObjectiveNeedsConnectionToggles() {
+        let mut config = ExperimentConfig::default();
+        config.multiObjective = true;
+        assert!(config.validate().is_err());
+
+        config.connectionToggleProbability = 0.1;
+        assert!(config.validate().is_ok());
+
+        config.algorithm = AlgorithmEnum::CMAES;
+        assert!(config.validate().is_err());
+    }
+}
@@ -3,6 +3,7 @@
 
 mod problems {
     pub mod naProblem;
+    pub mod countingProblem;
     pub mod cartpoleProblem;
     pub mod sphereContinuousProblem;
     pub mod sphereDiscreteProblem;
@@ -10,29 +11,56 @@ mod problems {
 mod utils {
     pub mod interval;
     pub mod mathUtils;
+    pub mod experimentContext;
 }
 mod mutationAlgorithms {
     pub mod mutationAlgorithm;
     pub mod gaussianMutation;
+    pub mod adaptiveGaussianMutation;
     pub mod localOnePlusOneNA;
     pub mod SAOnePlusOneNA;
     pub mod SALocalMutation;
     pub mod onePlusOneNA;
+    pub mod fastOnePlusOneNA;
+    pub mod populationStrategy;
+    pub mod crossover;
+    pub mod geneticAlgorithm;
+    pub mod CMAES;
+    pub mod simulatedAnnealing;
+    pub mod randomLocalSearch;
+    pub mod restartStrategy;
+    pub mod mapElites;
+    pub mod noveltySearch;
+    pub mod NSGAII;
 }
 
 pub mod ui;
+mod widgets {
+    pub mod fitnessPlot;
+}
+pub mod experiment;
+pub mod cli;
+pub mod suite;
 
 mod nn {
     pub mod ann;
+    pub mod activation;
+    pub mod networkFile;
 }
 use druid::{AppLauncher,WindowDesc};
 use ui::{AppState, build_ui};
 
-static mut R: f64 = 240.0;
-static mut OPTIMUM: f64 = 1.0;
-
-// Creates the graphical user interface window and its initial state
+// Runs the experiments headlessly when arguments are given, otherwise creates the graphical user interface window and its initial state
 pub fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(message) = cli::run(args) {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let window = WindowDesc::new(build_ui())
         .window_size((1000., 720.))
         .resizable(false)
@@ -0,0 +1,250 @@
+use std::fmt;
+
+use ndarray::{Array1, Array2, Axis};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{StandardNormal, Uniform};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::utils::experimentContext::ExperimentContext;
+use crate::utils::mathUtils;
+
+// Step size of the search distribution at the start of every restart
+const INITIAL_SIGMA: f64 = 0.5;
+// Restarts after which the population stops doubling, which keeps generations affordable on long runs
+const MAX_POPULATION_DOUBLINGS: u32 = 4;
+
+/*
+    Search distribution of CMA-ES, following Hansen's "The CMA Evolution Strategy: A Tutorial" (2016).
+    Candidates are drawn from N(mean, sigma² C), with C = B diag(D²) Bᵀ.
+ */
+struct SearchState {
+    lambda: usize,
+    // Recombination weights of the best μ = λ/2 candidates
+    weights: Array1<f64>,
+    mueff: f64,
+    cc: f64,
+    cs: f64,
+    c1: f64,
+    cmu: f64,
+    damps: f64,
+    // Expected length of a standard normal vector
+    chiN: f64,
+    mean: Array1<f64>,
+    sigma: f64,
+    covariance: Array2<f64>,
+    // Evolution paths of the covariance matrix and the step size
+    pc: Array1<f64>,
+    ps: Array1<f64>,
+    b: Array2<f64>,
+    d: Array1<f64>,
+    generation: u64,
+    // Evaluations since the covariance matrix was last decomposed, which is only done every few generations
+    evaluationsSinceDecomposition: usize,
+    bestScore: f64,
+    stalledGenerations: u64
+}
+
+impl SearchState {
+    fn new(mean: Array1<f64>, sigma: f64, lambda: usize) -> SearchState {
+        let dimensions = mean.len();
+        let n = dimensions as f64;
+        let mu = lambda / 2;
+        let rawWeights: Array1<f64> = (1..=mu).map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln()).collect();
+        let weights = &rawWeights / rawWeights.sum();
+        let mueff = 1.0 / weights.mapv(|w| w * w).sum();
+        let c1 = 2.0 / ((n + 1.3).powi(2) + mueff);
+        let cs = (mueff + 2.0) / (n + mueff + 5.0);
+        SearchState {
+            lambda,
+            weights,
+            mueff,
+            cc: (4.0 + mueff / n) / (n + 4.0 + 2.0 * mueff / n),
+            cs,
+            c1,
+            cmu: f64::min(1.0 - c1, 2.0 * (mueff - 2.0 + 1.0 / mueff) / ((n + 2.0).powi(2) + mueff)),
+            damps: 1.0 + 2.0 * f64::max(0.0, ((mueff - 1.0) / (n + 1.0)).sqrt() - 1.0) + cs,
+            chiN: n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n)),
+            mean,
+            sigma,
+            covariance: Array2::eye(dimensions),
+            pc: Array1::zeros(dimensions),
+            ps: Array1::zeros(dimensions),
+            b: Array2::eye(dimensions),
+            d: Array1::ones(dimensions),
+            generation: 0,
+            evaluationsSinceDecomposition: 0,
+            bestScore: f64::NEG_INFINITY,
+            stalledGenerations: 0
+        }
+    }
+
+    fn sample(&self, randGen: &mut StdRng) -> Array1<f64> {
+        let z: Array1<f64> = (0..self.mean.len()).map(|_| randGen.sample::<f64, _>(StandardNormal)).collect();
+        &self.mean + &(self.b.dot(&(&self.d * &z)) * self.sigma)
+    }
+
+    // Moves the distribution towards the candidates, sorted from best to worst
+    fn update(&mut self, sorted: &[Array1<f64>]) {
+        let n = self.mean.len() as f64;
+        let oldMean = self.mean.clone();
+        let steps: Vec<Array1<f64>> = sorted.iter().take(self.weights.len()).map(|x| (x - &oldMean) / self.sigma).collect();
+        let weightedStep = steps.iter().zip(&self.weights).fold(Array1::<f64>::zeros(oldMean.len()), |acc, (y, w)| acc + y * *w);
+        self.mean = &oldMean + &(&weightedStep * self.sigma);
+
+        // C^(-1/2) = B diag(1/D) Bᵀ
+        let whitenedStep = self.b.dot(&(&self.b.t().dot(&weightedStep) / &self.d));
+        self.ps = &self.ps * (1.0 - self.cs) + &(whitenedStep * (self.cs * (2.0 - self.cs) * self.mueff).sqrt());
+        self.generation += 1;
+        let psNorm = self.ps.dot(&self.ps).sqrt();
+        let hsig = psNorm / (1.0 - (1.0 - self.cs).powf(2.0 * self.generation as f64)).sqrt() / self.chiN < 1.4 + 2.0 / (n + 1.0);
+        let hsig = if hsig { 1.0 } else { 0.0 };
+        self.pc = &self.pc * (1.0 - self.cc) + &(&weightedStep * (hsig * (self.cc * (2.0 - self.cc) * self.mueff).sqrt()));
+
+        let rankOne = outer(&self.pc);
+        let rankMu = steps.iter().zip(&self.weights).fold(Array2::<f64>::zeros(self.covariance.dim()), |acc, (y, w)| acc + outer(y) * *w);
+        self.covariance = &self.covariance * (1.0 - self.c1 - self.cmu)
+            + (rankOne + &self.covariance * ((1.0 - hsig) * self.cc * (2.0 - self.cc))) * self.c1
+            + rankMu * self.cmu;
+        self.sigma *= ((self.cs / self.damps) * (psNorm / self.chiN - 1.0)).exp();
+
+        self.evaluationsSinceDecomposition += self.lambda;
+        if self.evaluationsSinceDecomposition as f64 > self.lambda as f64 / (self.c1 + self.cmu) / n / 10.0 {
+            self.evaluationsSinceDecomposition = 0;
+            self.covariance = (&self.covariance + &self.covariance.t()) / 2.0;
+            let (values, vectors) = mathUtils::symmetricEigen(&self.covariance);
+            self.d = values.mapv(|v| v.max(1e-20).sqrt());
+            self.b = vectors;
+        }
+    }
+
+    // The step size collapsed or exploded, the covariance matrix is ill-conditioned, or the best score stopped improving
+    fn needsRestart(&self) -> bool {
+        let maxD = self.d.fold(f64::MIN, |a, b| a.max(*b));
+        let minD = self.d.fold(f64::MAX, |a, b| a.min(*b));
+        !self.sigma.is_finite()
+            || self.sigma * maxD < 1e-12
+            || self.sigma > 1e8
+            || (maxD / minD).powi(2) > 1e14
+            || self.stalledGenerations as f64 > 10.0 + 30.0 * self.mean.len() as f64 / self.lambda as f64
+    }
+}
+
+fn outer(vector: &Array1<f64>) -> Array2<f64> {
+    let column = vector.view().insert_axis(Axis(1));
+    column.dot(&column.t())
+}
+
+/*
+    CMA-ES over the vector of all the network's weights and biases, starting from the initial network.
+    Each call to `mutate` is a generation of λ candidates, and the best one replaces the current network if it's at least as good,
+    so the run always holds the best network found so far.
+    The search restarts from a uniformly random mean in [-1, 1] with twice the population (IPOP-CMA-ES) when `needsRestart`.
+    With `rounded`, candidates are rounded to multiples of 1/r before being evaluated, the search space of the (1+1)NA algorithms,
+    while the distribution is updated with the unrounded values.
+ */
+pub struct CMAES {
+    problem: Box<dyn NAProblem>,
+    randGen: StdRng,
+    resolutionParameter: f64,
+    rounded: bool,
+    // Population of the first run, 4 + 3 ln(n)
+    defaultLambda: usize,
+    restarts: u32,
+    state: SearchState
+}
+
+impl CMAES {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, rounded: bool, seed: u64) -> Box<dyn MutationAlgorithm> {
+        let mean = nn.parameters();
+        let defaultLambda = 4 + (3.0 * (mean.len() as f64).ln()).floor() as usize;
+        Box::new(CMAES {
+            problem,
+            randGen: StdRng::seed_from_u64(seed),
+            resolutionParameter,
+            rounded,
+            defaultLambda,
+            restarts: 0,
+            state: SearchState::new(mean, INITIAL_SIGMA, defaultLambda)
+        })
+    }
+
+    fn toNetwork(&self, nn: &NANN, parameters: &Array1<f64>) -> NANN {
+        if self.rounded {
+            nn.withParameters(&parameters.mapv(|p| (p * self.resolutionParameter).round() / self.resolutionParameter))
+        } else {
+            nn.withParameters(parameters)
+        }
+    }
+
+    fn restart(&mut self) {
+        self.restarts += 1;
+        let uniform = Uniform::new_inclusive(-1.0, 1.0);
+        let mean: Array1<f64> = (0..self.state.mean.len()).map(|_| self.randGen.sample(uniform)).collect();
+        let lambda = self.defaultLambda * 2usize.pow(self.restarts.min(MAX_POPULATION_DOUBLINGS));
+        self.state = SearchState::new(mean, INITIAL_SIGMA, lambda);
+    }
+}
+
+impl fmt::Display for CMAES {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.rounded {
+            write!(f, "CMA-ES 1/R Grid")
+        } else {
+            write!(f, "CMA-ES")
+        }
+    }
+}
+
+impl MutationAlgorithm for CMAES {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        &self.problem
+    }
+
+    fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    // A single sample of the current distribution, which is only updated by `mutate`
+    fn vary(&mut self, nn: &NANN, _context: &ExperimentContext) -> Variation {
+        let parameters = self.state.sample(&mut self.randGen);
+        Variation {
+            nn: self.toNetwork(nn, &parameters),
+            mutatedParameters: parameters.len(),
+            toggledConnections: 0,
+            stepSizes: vec![self.state.sigma],
+            positions: vec![]
+        }
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        let sigma = self.state.sigma;
+        let mut candidates: Vec<(Array1<f64>, f64)> = Vec::with_capacity(self.state.lambda);
+        let mut best: Option<(NANN, Evaluation)> = None;
+        for _ in 0..self.state.lambda {
+            let parameters = self.state.sample(&mut self.randGen);
+            let candidate = self.toNetwork(&nn, &parameters);
+            let evaluation = self.problem.evaluate(&candidate, context);
+            candidates.push((parameters, evaluation.score));
+            if best.as_ref().map_or(true, |(_, bestEvaluation)| evaluation.score > bestEvaluation.score) {
+                best = Some((candidate, evaluation));
+            }
+        }
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        if candidates[0].1 > self.state.bestScore {
+            self.state.bestScore = candidates[0].1;
+            self.state.stalledGenerations = 0;
+        } else {
+            self.state.stalledGenerations += 1;
+        }
+        self.state.update(&candidates.into_iter().map(|(parameters, _)| parameters).collect::<Vec<Array1<f64>>>());
+        if self.state.needsRestart() {
+            self.restart();
+        }
+
+        let (bestNN, bestEvaluation) = best.unwrap();
+        let mutatedParameters = self.state.mean.len();
+        MutationResult::elitist(nn, bestNN, originalScore, bestEvaluation, mutatedParameters, 0, vec![sigma])
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, ParetoPoint, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::utils::experimentContext::ExperimentContext;
+
+struct Individual {
+    nn: NANN,
+    score: f64,
+    objectives: Vec<(&'static str, f64)>,
+    connections: usize,
+    // Objectives as they are compared, all maximised: the problem's objectives (or the score) and minus the number of connections
+    values: Vec<f64>
+}
+
+impl Individual {
+    fn new(nn: NANN, evaluation: &Evaluation) -> Individual {
+        let objectives = if evaluation.objectives.is_empty() { vec![("Score", evaluation.score)] } else { evaluation.objectives.clone() };
+        let connections = nn.connectionCount();
+        let mut values: Vec<f64> = objectives.iter().map(|(_, value)| *value).collect();
+        values.push(-(connections as f64));
+        Individual {
+            nn,
+            score: evaluation.score,
+            objectives,
+            connections,
+            values
+        }
+    }
+}
+
+// Whether `a` is at least as good as `b` in every objective and better in one
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+// Rank of every point: 0 for the non-dominated ones, 1 for the ones only dominated by rank 0, and so on (Deb et al.'s fast non-dominated sort)
+fn nonDominatedRanks(points: &[Vec<f64>]) -> Vec<usize> {
+    let mut dominatedPoints: Vec<Vec<usize>> = vec![vec![]; points.len()];
+    let mut dominationCounts = vec![0; points.len()];
+    for i in 0..points.len() {
+        for j in 0..points.len() {
+            if i != j && dominates(&points[i], &points[j]) {
+                dominatedPoints[i].push(j);
+                dominationCounts[j] += 1;
+            }
+        }
+    }
+    let mut ranks = vec![0; points.len()];
+    let mut front: Vec<usize> = (0..points.len()).filter(|i| dominationCounts[*i] == 0).collect();
+    let mut rank = 0;
+    while !front.is_empty() {
+        let mut nextFront = vec![];
+        for &i in &front {
+            ranks[i] = rank;
+            for &j in &dominatedPoints[i] {
+                dominationCounts[j] -= 1;
+                if dominationCounts[j] == 0 {
+                    nextFront.push(j);
+                }
+            }
+        }
+        front = nextFront;
+        rank += 1;
+    }
+    ranks
+}
+
+// Crowding distance of every point within its front: the sum over the objectives of the normalised gap between its two neighbours. The extremes get infinity
+fn crowdingDistances(points: &[Vec<f64>], ranks: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; points.len()];
+    let numberOfObjectives = points.first().map_or(0, |point| point.len());
+    for rank in 0..=ranks.iter().copied().max().unwrap_or(0) {
+        let front: Vec<usize> = (0..points.len()).filter(|i| ranks[*i] == rank).collect();
+        for m in 0..numberOfObjectives {
+            let mut sorted = front.clone();
+            sorted.sort_by(|a, b| points[*a][m].total_cmp(&points[*b][m]));
+            let (first, last) = (sorted[0], sorted[sorted.len() - 1]);
+            distances[first] = f64::INFINITY;
+            distances[last] = f64::INFINITY;
+            let range = points[last][m] - points[first][m];
+            if range > 0.0 {
+                for k in 1..sorted.len().saturating_sub(1) {
+                    distances[sorted[k]] += (points[sorted[k + 1]][m] - points[sorted[k - 1]][m]) / range;
+                }
+            }
+        }
+    }
+    distances
+}
+
+/*
+    NSGA-II (Deb et al., 2002), using another mutation algorithm's variation as its mutation operator.
+    Networks are compared on the problem's separate objectives (true positives and true negatives for the spheres, the score for cartpole)
+    and on their number of connections, which is minimised.
+    Every generation, n offspring are created from parents chosen by binary tournaments on (rank, crowding distance),
+    and the next n parents are the best of parents and offspring by non-dominated rank, then crowding distance.
+    The first generation mutates the network of the first generation n times.
+ */
+pub struct NSGAII {
+    operator: Box<dyn MutationAlgorithm>,
+    randGen: StdRng,
+    populationSize: usize,
+    population: Vec<Individual>,
+    // Non-dominated rank and crowding distance of every parent, in the order of `population`
+    ranks: Vec<usize>,
+    crowding: Vec<f64>
+}
+
+impl NSGAII {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, populationSize: usize, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(NSGAII {
+            operator,
+            randGen: StdRng::seed_from_u64(seed),
+            populationSize,
+            population: vec![],
+            ranks: vec![],
+            crowding: vec![]
+        })
+    }
+
+    // Index of the better of two random parents: the lower rank wins, then the larger crowding distance
+    fn tournament(&mut self) -> usize {
+        let first = self.randGen.gen_range(0..self.population.len());
+        let second = self.randGen.gen_range(0..self.population.len());
+        let better = self.ranks[first] < self.ranks[second] || (self.ranks[first] == self.ranks[second] && self.crowding[first] >= self.crowding[second]);
+        if better { first } else { second }
+    }
+}
+
+impl fmt::Display for NSGAII {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NSGA-II-{} {}", self.populationSize, self.operator)
+    }
+}
+
+impl MutationAlgorithm for NSGAII {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    fn reset(&mut self) {
+        self.population.clear();
+        self.ranks.clear();
+        self.crowding.clear();
+        self.operator.reset();
+    }
+
+    // Distinct members of the first front
+    fn paretoFront(&self) -> Vec<ParetoPoint> {
+        let mut front: Vec<&Individual> = vec![];
+        for (individual, rank) in self.population.iter().zip(&self.ranks) {
+            if *rank == 0 && !front.iter().any(|member| member.values == individual.values) {
+                front.push(individual);
+            }
+        }
+        front.sort_by(|a, b| b.score.total_cmp(&a.score));
+        front.into_iter().map(|individual| ParetoPoint {
+            score: individual.score,
+            connections: individual.connections,
+            objectives: individual.objectives.clone()
+        }).collect()
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        // The best-scoring offspring is reported as the generation's candidate
+        let mut candidate: Option<(NANN, Evaluation, usize, usize, Vec<f64>)> = None;
+        let mut offspring = Vec::with_capacity(self.populationSize);
+        for _ in 0..self.populationSize {
+            let parent = if self.population.is_empty() { None } else { Some(self.tournament()) };
+            let (parentNN, parentScore) = match parent {
+                Some(index) => (&self.population[index].nn, self.population[index].score),
+                None => (&nn, originalScore)
+            };
+            let variation = self.operator.vary(parentNN, context);
+            let evaluation = self.operator.getProblem().evaluate(&variation.nn, context);
+            self.operator.adapt(&variation, evaluation.score > parentScore);
+
+            offspring.push(Individual::new(variation.nn.clone(), &evaluation));
+            if candidate.as_ref().map_or(true, |(_, best, _, _, _)| evaluation.score > best.score) {
+                candidate = Some((variation.nn, evaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes));
+            }
+        }
+
+        let mut pool = offspring;
+        pool.append(&mut self.population);
+        let values: Vec<Vec<f64>> = pool.iter().map(|individual| individual.values.clone()).collect();
+        let ranks = nonDominatedRanks(&values);
+        let crowding = crowdingDistances(&values, &ranks);
+        let mut ranked: Vec<(usize, f64, Individual)> = ranks.into_iter().zip(crowding).zip(pool).map(|((rank, distance), individual)| (rank, distance, individual)).collect();
+        ranked.sort_by(|(rankA, distanceA, _), (rankB, distanceB, _)| rankA.cmp(rankB).then(distanceB.total_cmp(distanceA)));
+        ranked.truncate(self.populationSize);
+        self.ranks = ranked.iter().map(|(rank, _, _)| *rank).collect();
+        self.crowding = ranked.iter().map(|(_, distance, _)| *distance).collect();
+        self.population = ranked.into_iter().map(|(_, _, individual)| individual).collect();
+
+        let (mutatedNN, evaluation, mutatedParameters, toggledConnections, stepSizes) = candidate.unwrap();
+        MutationResult::elitist(nn, mutatedNN, originalScore, evaluation, mutatedParameters, toggledConnections, stepSizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranksFollowDomination() {
+        let points = vec![vec![1.0, 1.0], vec![3.0, 0.0], vec![0.0, 3.0], vec![0.5, 0.5], vec![0.0, 0.0], vec![1.0, 1.0]];
+        assert_eq!(nonDominatedRanks(&points), vec![0, 0, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn crowdingDistancesAreComputedPerFront() {
+        let points = vec![vec![0.0, 4.0], vec![1.0, 2.0], vec![3.0, 1.0], vec![4.0, 0.0], vec![0.0, 0.0]];
+        let ranks = nonDominatedRanks(&points);
+        assert_eq!(ranks, vec![0, 0, 0, 0, 1]);
+        let distances = crowdingDistances(&points, &ranks);
+        assert_eq!(distances[0], f64::INFINITY);
+        assert_eq!(distances[3], f64::INFINITY);
+        // (3 - 0) / 4 + (4 - 1) / 4 and (4 - 1) / 4 + (2 - 0) / 4
+        assert!((distances[1] - 1.5).abs() < 1e-12);
+        assert!((distances[2] - 1.25).abs() < 1e-12);
+        // Alone in its front
+        assert_eq!(distances[4], f64::INFINITY);
+    }
+}
@@ -1,6 +1,8 @@
-use crate::{mutationAlgorithms::mutationAlgorithm::MutationAlgorithm, utils::mathUtils};
+use crate::{mutationAlgorithms::mutationAlgorithm::{drawPosition, MutationAlgorithm, Variation}, utils::mathUtils};
 use crate::problems::naProblem::NAProblem;
 use crate::nn::ann::NANN;
+use rand::{rngs::StdRng, SeedableRng};
+use crate::utils::experimentContext::ExperimentContext;
 use ndarray::{Array2, Array1};
 use rand::Rng;
 use rand_distr::{Uniform, Bernoulli, Distribution};
@@ -8,16 +10,18 @@ use std::fmt;
 
 pub struct SAOnePlusOneNA {
     problem: Box<dyn NAProblem>,
+    randGen: StdRng,
     resolutionParameter: f64,
     numberOfNeurons: u32,
     weightStepSizes: Vec<Array2<f64>>,
     biasStepSizes: Vec<Array1<f64>>,
     successAdaptation: f64,
-    failureAdaptation: f64
+    failureAdaptation: f64,
+    toggleProbability: f64
 }
 
 impl SAOnePlusOneNA {
-    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, successAdaptation: f64, failureAdaptation: f64) -> Box<dyn MutationAlgorithm> {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, successAdaptation: f64, failureAdaptation: f64, toggleProbability: f64, seed: u64) -> Box<dyn MutationAlgorithm> {
         assert!(successAdaptation > 1.0);
         assert!(failureAdaptation > 0.0 && failureAdaptation < 1.0);
         let mut weightStepSizes: Vec<Array2<f64>> = vec![];
@@ -30,12 +34,14 @@ impl SAOnePlusOneNA {
         }
         Box::new(SAOnePlusOneNA {
             problem,
+            randGen: StdRng::seed_from_u64(seed),
             weightStepSizes,
             resolutionParameter,
             numberOfNeurons: nn.layers.iter().fold(0, |total, l| total + l.getBiases().len()) as u32,
             biasStepSizes,
             successAdaptation,
-            failureAdaptation
+            failureAdaptation,
+            toggleProbability
         })
     }
 }
@@ -51,48 +57,65 @@ impl MutationAlgorithm for SAOnePlusOneNA {
     fn getProblem(&self) -> &Box<dyn NAProblem> {
         &self.problem
     }
+
+    fn reset(&mut self) {
+        self.weightStepSizes.iter_mut().for_each(|stepSizes| stepSizes.fill(self.resolutionParameter/8.0));
+        self.biasStepSizes.iter_mut().for_each(|stepSizes| stepSizes.fill(self.resolutionParameter/8.0));
+    }
     
-    fn mutate(&mut self, nn: NANN, originalScore: f64) -> NANN {
-        let mut randGen = rand::thread_rng();
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        let randGen = &mut self.randGen;
         
         let mut mutatedNN = nn.clone();
 
         let mut mutatedParameters: Vec<(usize, usize, usize)> = vec![];
+        let mut stepSizes: Vec<f64> = vec![];
+        let mut toggledConnections = 0;
         let bernoulli = Bernoulli::from_ratio(1, self.numberOfNeurons).unwrap();
         let uniform = Uniform::new_inclusive(-1.0, 1.0);
 
         // At least one parameter is always mutated
         loop {
-            // Draw what parameter is mutated. i is the layer, x and y correspond to the coordinates in that layer's matrix
-            let i = randGen.gen_range(0..mutatedNN.layers.len());
-            let x = randGen.gen_range(0..self.weightStepSizes[i].nrows());
-            let y = randGen.gen_range(0..self.weightStepSizes[i].ncols());
-            mutatedNN.layers[i].weights[(x, y)] += (mathUtils::harmonicDistribution() * (self.weightStepSizes[i][(x, y)] * randGen.sample::<f64, Uniform<f64>>(uniform).signum())) / self.resolutionParameter;
-            if mutatedNN.isUsingBias() {
-                mutatedNN.layers[i].biases[(y)] += (mathUtils::harmonicDistribution() *(self.biasStepSizes[i][(y)] * randGen.sample::<f64, Uniform<f64>>(uniform).signum())) / self.resolutionParameter;
-            }
+            // Draw what parameter is mutated, unless its connection is toggled instead
+            let ((i, x, y), toggled) = drawPosition(&mut mutatedNN, self.toggleProbability, randGen);
+            if toggled {
+                toggledConnections += 1;
+            } else {
+                mutatedNN.layers[i].weights[(x, y)] += (mathUtils::harmonicDistribution(context.resolution, randGen) * (self.weightStepSizes[i][(x, y)] * randGen.sample::<f64, Uniform<f64>>(uniform).signum())) / self.resolutionParameter;
+                if mutatedNN.isUsingBias() {
+                    mutatedNN.layers[i].biases[(y)] += (mathUtils::harmonicDistribution(context.resolution, randGen) *(self.biasStepSizes[i][(y)] * randGen.sample::<f64, Uniform<f64>>(uniform).signum())) / self.resolutionParameter;
+                }
 
-            // Store the mutated parameters for self-adaptation
-            mutatedParameters.push((i, x, y));
+                // Store the mutated parameters for self-adaptation, and the step sizes used on them
+                mutatedParameters.push((i, x, y));
+                stepSizes.push(self.weightStepSizes[i][(x, y)]);
+                if mutatedNN.isUsingBias() {
+                    stepSizes.push(self.biasStepSizes[i][y]);
+                }
+            }
                                     
             if !randGen.sample(bernoulli) {
                 break;
             }
         }
 
-        let (_, mutatedScore, _) = self.problem.evaluate(&mutatedNN);
-        // Go through each mutated parameter and adjust their mutation strength
-        mutatedParameters.iter().for_each(|(i, x, y)| {
-            let adaptationStrength = if mutatedScore > originalScore { self.successAdaptation } else { self.failureAdaptation };
+        Variation {
+            nn: mutatedNN,
+            mutatedParameters: stepSizes.len(),
+            toggledConnections,
+            stepSizes,
+            positions: mutatedParameters
+        }
+    }
+
+    // Go through each mutated parameter and adjust their mutation strength
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        let adaptationStrength = if improved { self.successAdaptation } else { self.failureAdaptation };
+        variation.positions.iter().for_each(|(i, x, y)| {
             self.weightStepSizes[*i][(*x, *y)] = f64::max(1.0, self.weightStepSizes[*i][(*x, *y)] * adaptationStrength);
-            if nn.isUsingBias() {
+            if variation.nn.isUsingBias() {
                 self.biasStepSizes[*i][(*y)] = f64::max(1.0, self.biasStepSizes[*i][(*y)] * adaptationStrength);
             }
         });
-        if mutatedScore >= originalScore {
-            mutatedNN
-        } else {
-            nn
-        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use ndarray::Array1;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::NAProblem;
+use crate::utils::experimentContext::ExperimentContext;
+
+// Step size before any adaptation, the fixed standard deviation of GaussianMutation
+const INITIAL_SIGMA: f64 = 0.5;
+const MIN_SIGMA: f64 = 1e-8;
+
+pub struct AdaptiveGaussianMutation {
+    problem: Box<dyn NAProblem>,
+    randGen: StdRng,
+    // Global step size, adapted with the 1/5th success rule
+    sigma: f64,
+    // Per-parameter step size multipliers, in the order of NANN::parameters. Only adapted when `logNormal` is set
+    scales: Array1<f64>,
+    logNormal: bool,
+    // 1/(number of neurons in the parameter's layer), in the order of NANN::parameters
+    mutationProbabilities: Array1<f64>,
+    // Multipliers tried by the last variation, kept if it improved
+    proposedScales: Vec<(usize, f64)>
+}
+
+impl AdaptiveGaussianMutation {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, logNormal: bool, seed: u64) -> Box<dyn MutationAlgorithm> {
+        let mutationProbabilities: Array1<f64> = nn.layers.iter().flat_map(|layer| {
+            let parameters = layer.weights.len() + if nn.isUsingBias() { layer.biases.len() } else { 0 };
+            vec![1.0 / layer.biases.len() as f64; parameters]
+        }).collect();
+        Box::new(AdaptiveGaussianMutation {
+            problem,
+            randGen: StdRng::seed_from_u64(seed),
+            sigma: INITIAL_SIGMA,
+            scales: Array1::ones(mutationProbabilities.len()),
+            logNormal,
+            mutationProbabilities,
+            proposedScales: vec![]
+        })
+    }
+}
+
+impl fmt::Display for AdaptiveGaussianMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.logNormal {
+            write!(f, "Gaussian 1/5th Rule Log-Normal")
+        } else {
+            write!(f, "Gaussian 1/5th Rule")
+        }
+    }
+}
+
+impl MutationAlgorithm for AdaptiveGaussianMutation {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        &self.problem
+    }
+
+    /*
+     * Each parameter is mutated with a probability of 1/number of neurons in its layer (at least one always is),
+     * by a value drawn from a Normal distribution with standard deviation sigma times the parameter's multiplier.
+     * With log-normal self-adaptation, the multipliers of the mutated parameters are themselves mutated first:
+     * s_i' = s_i * exp(tau' * N(0, 1) + tau * N_i(0, 1)), with tau' = 1/sqrt(2n) and tau = 1/sqrt(2 sqrt(n))
+     */
+    fn vary(&mut self, nn: &NANN, _context: &ExperimentContext) -> Variation {
+        let randGen = &mut self.randGen;
+        let mut parameters = nn.parameters();
+        let n = parameters.len() as f64;
+        let globalLearningRate = 1.0 / (2.0 * n).sqrt();
+        let localLearningRate = 1.0 / (2.0 * n.sqrt()).sqrt();
+        let globalFactor = globalLearningRate * randGen.sample::<f64, _>(StandardNormal);
+
+        let mut mutated: Vec<usize> = (0..parameters.len()).filter(|i| randGen.gen::<f64>() < self.mutationProbabilities[*i]).collect();
+        if mutated.is_empty() {
+            mutated.push(randGen.gen_range(0..parameters.len()));
+        }
+        self.proposedScales.clear();
+        let mut stepSizes = vec![];
+        for i in mutated {
+            let scale = if self.logNormal {
+                self.scales[i] * (globalFactor + localLearningRate * randGen.sample::<f64, _>(StandardNormal)).exp()
+            } else {
+                self.scales[i]
+            };
+            let stepSize = self.sigma * scale;
+            parameters[i] += stepSize * randGen.sample::<f64, _>(StandardNormal);
+            self.proposedScales.push((i, scale));
+            stepSizes.push(stepSize);
+        }
+
+        Variation {
+            nn: nn.withParameters(&parameters),
+            mutatedParameters: stepSizes.len(),
+            toggledConnections: 0,
+            stepSizes,
+            positions: vec![]
+        }
+    }
+
+    /*
+     * Rechenberg's 1/5th success rule: sigma grows by exp(1/3) after an improvement and shrinks by exp(-1/12) otherwise,
+     * so it stays constant when one in five mutations improves.
+     * Multipliers tried by an improving mutation replace the current ones.
+     */
+    fn adapt(&mut self, _variation: &Variation, improved: bool) {
+        if improved {
+            self.sigma *= (1.0f64 / 3.0).exp();
+            for (i, scale) in self.proposedScales.drain(..) {
+                self.scales[i] = scale;
+            }
+        } else {
+            self.sigma = f64::max(MIN_SIGMA, self.sigma * (-1.0f64 / 12.0).exp());
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sigma = INITIAL_SIGMA;
+        self.scales.fill(1.0);
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use druid::Data;
+use ndarray::Zip;
+use rand::Rng;
+
+use crate::nn::ann::NANN;
+
+// Recombination of two networks with the same layer sizes into a child network
+#[derive(Data, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Crossover {
+    // Every weight (with its connection mask bit) and bias is taken from either parent with equal probability
+    Uniform,
+    // Every neuron, its incoming weights and connections (a column of the layer's weights and mask) and its bias, is taken from either parent
+    Neuron,
+    // Every layer is taken as a whole from either parent
+    Layer
+}
+
+impl Crossover {
+    // Every crossover operator, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, Crossover)> {
+        vec![
+            ("Uniform", Crossover::Uniform),
+            ("Neuron", Crossover::Neuron),
+            ("Layer", Crossover::Layer),
+        ]
+    }
+
+    // Looks up a crossover operator by its name, ignoring case
+    pub fn fromName(name: &str) -> Option<Crossover> {
+        Crossover::all().into_iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(name))
+            .map(|(_, crossover)| crossover)
+    }
+
+    // Child of `first` and `second`, which must have the same layer sizes. The child uses the first parent's activations and bias flag
+    pub fn recombine(&self, first: &NANN, second: &NANN, randGen: &mut impl Rng) -> NANN {
+        assert_eq!(first.layerSizes(), second.layerSizes(), "Only networks with the same layer sizes can be recombined");
+        let mut child = first.clone();
+        for (layer, other) in child.layers.iter_mut().zip(&second.layers) {
+            match self {
+                Crossover::Uniform => {
+                    // A weight and its mask bit come from the same parent, so the child never re-enables a connection the donor pruned
+                    Zip::from(&mut layer.weights).and(&mut layer.mask).and(&other.weights).and(&other.mask).for_each(|weight, enabled, otherWeight, otherEnabled| {
+                        if randGen.gen_bool(0.5) {
+                            *weight = *otherWeight;
+                            *enabled = *otherEnabled;
+                        }
+                    });
+                    layer.biases.zip_mut_with(&other.biases, |x, y| if randGen.gen_bool(0.5) { *x = *y });
+                },
+                Crossover::Neuron => {
+                    for neuron in 0..layer.biases.len() {
+                        if randGen.gen_bool(0.5) {
+                            layer.weights.column_mut(neuron).assign(&other.weights.column(neuron));
+                            layer.mask.column_mut(neuron).assign(&other.mask.column(neuron));
+                            layer.biases[neuron] = other.biases[neuron];
+                        }
+                    }
+                },
+                Crossover::Layer => {
+                    if randGen.gen_bool(0.5) {
+                        layer.weights.assign(&other.weights);
+                        layer.mask.assign(&other.mask);
+                        layer.biases.assign(&other.biases);
+                    }
+                }
+            }
+        }
+        child
+    }
+}
+
+impl fmt::Display for Crossover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Crossover::all().into_iter().find(|(_, crossover)| crossover == self).map_or("", |(label, _)| label);
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::mutationAlgorithms::mutationAlgorithm::{drawPosition, MutationAlgorithm, Variation};
+use crate::problems::naProblem::NAProblem;
+use crate::nn::ann::NANN;
+use rand::{rngs::StdRng, SeedableRng};
+use crate::utils::experimentContext::ExperimentContext;
+use crate::utils::mathUtils;
+use rand::Rng;
+use rand_distr::Uniform;
+
+/*
+    Heavy-tailed ("fast") variants of the (1+1)NA, following the fast GA of Doerr et al. (2017).
+    The number of mutated parameters is drawn from a power law with exponent beta over 1..number of weights, instead of being geometric.
+    The non-local variant also draws the step size from a power law over 1..R, generalising the harmonic distribution (beta = 1),
+    while the local variant always steps by 1/R like the Local (1+1)NA.
+ */
+pub struct FastOnePlusOneNA {
+    problem: Box<dyn NAProblem>,
+    randGen: StdRng,
+    resolutionParameter: f64,
+    beta: f64,
+    local: bool,
+    // Cumulative power laws over 1..number of weights and 1..R, built once
+    mutationCounts: Vec<f64>,
+    stepSizes: Vec<f64>,
+    toggleProbability: f64
+}
+
+impl FastOnePlusOneNA {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, beta: f64, local: bool, toggleProbability: f64, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(FastOnePlusOneNA {
+            problem,
+            randGen: StdRng::seed_from_u64(seed),
+            resolutionParameter,
+            beta,
+            local,
+            mutationCounts: mathUtils::powerLawTable(nn.layers.iter().map(|l| l.weights.len()).sum::<usize>() as f64, beta),
+            stepSizes: mathUtils::powerLawTable(resolutionParameter, beta),
+            toggleProbability
+        })
+    }
+}
+
+impl fmt::Display for FastOnePlusOneNA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.local {
+            write!(f, "Fast Local (1+1)NA beta={}", self.beta)
+        } else {
+            write!(f, "Fast (1+1)NA beta={}", self.beta)
+        }
+    }
+}
+
+impl MutationAlgorithm for FastOnePlusOneNA {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        &self.problem
+    }
+
+    fn vary(&mut self, nn: &NANN, _context: &ExperimentContext) -> Variation {
+        let randGen = &mut self.randGen;
+        let mut mutatedNN = nn.clone();
+        let uniform = Uniform::new_inclusive(-1.0, 1.0);
+        let numberOfMutations = mathUtils::powerLawDistribution(&self.mutationCounts, randGen) as usize;
+
+        let mut mutatedParameters = 0;
+        let mut toggledConnections = 0;
+        let mut stepSizes = vec![];
+        for _ in 0..numberOfMutations {
+            // Draw what parameter is mutated, unless its connection is toggled instead
+            let ((i, x, y), toggled) = drawPosition(&mut mutatedNN, self.toggleProbability, randGen);
+            if toggled {
+                toggledConnections += 1;
+            } else {
+                let step = if self.local { 1.0 } else { mathUtils::powerLawDistribution(&self.stepSizes, randGen) };
+                mutatedNN.layers[i].weights[(x, y)] += (step / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
+                stepSizes.push(step);
+                if mutatedNN.isUsingBias() {
+                    let step = if self.local { 1.0 } else { mathUtils::powerLawDistribution(&self.stepSizes, randGen) };
+                    mutatedNN.layers[i].biases[y] += (step / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
+                    stepSizes.push(step);
+                }
+                mutatedParameters += if mutatedNN.isUsingBias() { 2 } else { 1 };
+            }
+        }
+
+        Variation {
+            nn: mutatedNN,
+            mutatedParameters,
+            toggledConnections,
+            stepSizes,
+            positions: vec![]
+        }
+    }
+}
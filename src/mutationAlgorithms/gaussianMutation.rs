@@ -1,17 +1,21 @@
-use crate::mutationAlgorithms::mutationAlgorithm::MutationAlgorithm;
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, Variation};
 use crate::nn::ann::NANN;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::utils::experimentContext::ExperimentContext;
 use rand_distr::{Normal, Distribution};
 use crate::problems::naProblem::NAProblem;
 use std::fmt;
 
 pub struct GaussianMutation {
-    problem: Box<dyn NAProblem>
+    problem: Box<dyn NAProblem>,
+    randGen: StdRng
 }
 
 impl GaussianMutation {
-    pub fn new(problem: Box<dyn NAProblem>) ->  Box<dyn MutationAlgorithm> {
+    pub fn new(problem: Box<dyn NAProblem>, seed: u64) -> Box<dyn MutationAlgorithm> {
         Box::new(GaussianMutation {
-            problem
+            problem,
+            randGen: StdRng::seed_from_u64(seed)
         })
     }
 }
@@ -30,23 +34,25 @@ impl MutationAlgorithm for GaussianMutation {
     /*
      * For each parameter, with a probability of 1/number of neurons in the layer, mutate the parameter by a random value from a Standard Normal Distribution
      */
-    fn mutate(&mut self, nn: NANN, originalScore: f64) -> NANN {
-        let mut randGen = rand::thread_rng();
+    fn vary(&mut self, nn: &NANN, _context: &ExperimentContext) -> Variation {
+        let randGen = &mut self.randGen;
         let gaussGenerator = Normal::<f64>::new(0.0, 0.5).unwrap();
         let mut mutatedNN = nn.clone();
+        let mut mutatedParameters = 0;
         for i in 0..mutatedNN.layers.len() {
             let layer = mutatedNN.layers[i].clone();
             let probOfMutation = 1.0 / layer.biases.dim() as f32;
-            mutatedNN.layers[i].weights = layer.weights.map(|x| if rand::random::<f32>() < probOfMutation { x + gaussGenerator.sample(&mut randGen) } else { *x });
+            mutatedNN.layers[i].weights = layer.weights.map(|x| if randGen.gen::<f32>() < probOfMutation { mutatedParameters += 1; x + gaussGenerator.sample(&mut *randGen) } else { *x });
             if mutatedNN.isUsingBias() {
-                mutatedNN.layers[i].biases = layer.biases.map(|x| if rand::random::<f32>() < probOfMutation {  x + gaussGenerator.sample(&mut randGen)} else {*x});
+                mutatedNN.layers[i].biases = layer.biases.map(|x| if randGen.gen::<f32>() < probOfMutation { mutatedParameters += 1; x + gaussGenerator.sample(&mut *randGen)} else {*x});
             }
         }
-        let (_, mutatedScore, _) = self.problem.evaluate(&mutatedNN);
-        if mutatedScore >= originalScore {
-            mutatedNN
-        } else {
-            nn
+        Variation {
+            nn: mutatedNN,
+            mutatedParameters,
+            toggledConnections: 0,
+            stepSizes: vec![],
+            positions: vec![]
         }
     }
 
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::crossover::Crossover;
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::utils::experimentContext::ExperimentContext;
+
+struct Individual {
+    id: u64,
+    nn: NANN,
+    score: f64
+}
+
+/*
+    Generational genetic algorithm, using a crossover operator for recombination and another mutation algorithm's variation as its mutation step.
+    Every generation, the best individual is kept and the rest of the population is replaced by offspring. Each offspring recombines
+    two parents chosen by binary tournament with probability `crossoverRate` (otherwise it copies the first one), and is then mutated.
+    Self-adaptive operators adapt their step sizes by comparing every offspring with its first parent.
+    The population starts from the network of the first generation alone, and is filled by the first generation's offspring.
+    Each call to `mutate` is a whole generation, and returns the best individual. It counts as accepted whenever the best individual changes.
+ */
+pub struct GeneticAlgorithm {
+    operator: Box<dyn MutationAlgorithm>,
+    crossover: Crossover,
+    populationSize: usize,
+    crossoverRate: f64,
+    // Sorted from best to worst score
+    population: Vec<Individual>,
+    nextId: u64,
+    randGen: StdRng
+}
+
+impl GeneticAlgorithm {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, crossover: Crossover, populationSize: usize, crossoverRate: f64, seed: u64) -> Box<dyn MutationAlgorithm> {
+        assert!(populationSize >= 2, "A genetic algorithm needs a population of at least 2");
+        Box::new(GeneticAlgorithm {
+            operator,
+            crossover,
+            populationSize,
+            crossoverRate,
+            population: vec![],
+            nextId: 0,
+            randGen: StdRng::seed_from_u64(seed)
+        })
+    }
+
+    fn newIndividual(&mut self, nn: NANN, score: f64) -> Individual {
+        self.nextId += 1;
+        Individual {
+            id: self.nextId,
+            nn,
+            score
+        }
+    }
+
+    // Index of the better of two individuals drawn uniformly at random
+    fn tournament(&mut self) -> usize {
+        let first = self.randGen.gen_range(0..self.population.len());
+        let second = self.randGen.gen_range(0..self.population.len());
+        if self.population[second].score > self.population[first].score { second } else { first }
+    }
+}
+
+impl fmt::Display for GeneticAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GA-{} {} Crossover {}", self.populationSize, self.crossover, self.operator)
+    }
+}
+
+impl MutationAlgorithm for GeneticAlgorithm {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    // The population is rebuilt from the next network
+    fn reset(&mut self) {
+        self.population.clear();
+        self.operator.reset();
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        if self.population.is_empty() {
+            let individual = self.newIndividual(nn, originalScore);
+            self.population.push(individual);
+        }
+        let previousBest = self.population[0].id;
+
+        let mut candidate: Option<(Evaluation, usize, usize, Vec<f64>)> = None;
+        let mut offspring = Vec::with_capacity(self.populationSize);
+        for _ in 1..self.populationSize {
+            let first = self.tournament();
+            let second = self.tournament();
+            let child = if self.randGen.gen_bool(self.crossoverRate) {
+                self.crossover.recombine(&self.population[first].nn, &self.population[second].nn, &mut self.randGen)
+            } else {
+                self.population[first].nn.clone()
+            };
+            let variation = self.operator.vary(&child, context);
+            let evaluation = self.operator.getProblem().evaluate(&variation.nn, context);
+            self.operator.adapt(&variation, evaluation.score > self.population[first].score);
+
+            let child = self.newIndividual(variation.nn, evaluation.score);
+            if candidate.as_ref().map_or(true, |(best, _, _, _)| evaluation.score > best.score) {
+                candidate = Some((evaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes));
+            }
+            offspring.push(child);
+        }
+
+        // The elite goes last so offspring win ties against it, as in the elitist (1+1) schemes.
+        // A new best individual is therefore always the candidate
+        let elite = self.population.swap_remove(0);
+        offspring.push(elite);
+        offspring.sort_by(|a, b| b.score.total_cmp(&a.score));
+        self.population = offspring;
+
+        let (evaluation, mutatedParameters, toggledConnections, stepSizes) = candidate.unwrap();
+        let best = &self.population[0];
+        MutationResult {
+            nn: best.nn.clone(),
+            accepted: best.id != previousBest,
+            evaluation,
+            mutatedParameters,
+            toggledConnections,
+            stepSizes
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{ArchiveCell, MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::NAProblem;
+use crate::utils::experimentContext::ExperimentContext;
+
+struct Elite {
+    nn: NANN,
+    score: f64
+}
+
+/*
+    MAP-Elites (Mouret & Clune, 2015), using another mutation algorithm's variation as its mutation operator.
+    The problem's behaviour space [0, 1]^2 is split into a grid, and the archive keeps the best network found in every cell.
+    Every generation, one elite drawn uniformly at random from the archive is mutated, and its offspring replaces the elite of its cell
+    if that cell is empty or the offspring is at least as good. The archive starts with the network of the first generation.
+    The offspring is kept as the run's network if it's at least as good as the best one so far, like the (1+1) schemes.
+ */
+pub struct MapElites {
+    operator: Box<dyn MutationAlgorithm>,
+    randGen: StdRng,
+    cellsPerDimension: usize,
+    // Ordered by cell, so drawing an elite only depends on the seed
+    elites: BTreeMap<Vec<usize>, Elite>
+}
+
+impl MapElites {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, cellsPerDimension: usize, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(MapElites {
+            operator,
+            randGen: StdRng::seed_from_u64(seed),
+            cellsPerDimension,
+            elites: BTreeMap::new()
+        })
+    }
+}
+
+impl fmt::Display for MapElites {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MAP-Elites {}", self.operator)
+    }
+}
+
+impl MutationAlgorithm for MapElites {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    fn reset(&mut self) {
+        self.elites.clear();
+        self.operator.reset();
+    }
+
+    fn archive(&self) -> Vec<ArchiveCell> {
+        self.elites.iter().map(|(cell, elite)| ArchiveCell { cell: cell.clone(), score: elite.score }).collect()
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        if self.elites.is_empty() {
+            // Only the score of the initial network is passed in, so it's evaluated once more to find its cell
+            let behaviour = self.operator.getProblem().evaluate(&nn, context).behaviour;
+            self.elites.insert(ArchiveCell::cellOf(&behaviour, self.cellsPerDimension), Elite { nn: nn.clone(), score: originalScore });
+        }
+        let index = self.randGen.gen_range(0..self.elites.len());
+        let parent = self.elites.values().nth(index).unwrap();
+        let variation = self.operator.vary(&parent.nn, context);
+        let evaluation = self.operator.getProblem().evaluate(&variation.nn, context);
+        self.operator.adapt(&variation, evaluation.score > parent.score);
+
+        let cell = ArchiveCell::cellOf(&evaluation.behaviour, self.cellsPerDimension);
+        if self.elites.get(&cell).map_or(true, |elite| evaluation.score >= elite.score) {
+            self.elites.insert(cell, Elite { nn: variation.nn.clone(), score: evaluation.score });
+        }
+        MutationResult::elitist(nn, variation.nn, originalScore, evaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes)
+    }
+}
@@ -1,11 +1,140 @@
 use std::fmt;
 
-use crate::{nn::ann::NANN, problems::naProblem::NAProblem};
+use rand::Rng;
+
+use crate::{nn::ann::NANN, problems::naProblem::{Evaluation, NAProblem}, utils::experimentContext::ExperimentContext};
 /*
     Base trait for all mutation algorithms
  */
-pub trait MutationAlgorithm: fmt::Display {
+pub trait MutationAlgorithm: fmt::Display + Send {
     fn getProblem(&self) -> &Box<dyn NAProblem>;
+
+    // Creates a mutated copy of `nn`, without evaluating it
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation;
+
+    // Tells the algorithm whether a variation scored better than the network it was created from. Used by the self-adaptive algorithms
+    fn adapt(&mut self, _variation: &Variation, _improved: bool) {}
+
+    // Forgets everything learned so far (step sizes, populations, temperatures), as if the next network were the first one. Called by RestartStrategy
+    fn reset(&mut self) {}
+
+    // Number of times the search restarted from a new network, for the algorithms that restart
+    fn restarts(&self) -> u32 {
+        0
+    }
+
+    // Behaviour archive built during the run, for MAP-Elites and novelty search. Empty otherwise
+    fn archive(&self) -> Vec<ArchiveCell> {
+        vec![]
+    }
+
+    // Non-dominated networks of the final population, for NSGA-II. Empty otherwise
+    fn paretoFront(&self) -> Vec<ParetoPoint> {
+        vec![]
+    }
     
-    fn mutate(&mut self, nn: NANN, originalScore: f64) -> NANN;
+    /*
+        Creates and evaluates a mutated candidate from `nn`, whose score is `originalScore`, and keeps the candidate if it's at least as good.
+        The original network must not be evaluated again: the run loop carries its evaluation forward,
+        and replaces it with the candidate's evaluation only if the candidate is accepted.
+        MAP-Elites is the one exception: it evaluates the first network again to place it in its archive.
+        Algorithms that create several candidates per generation return the best-scoring one as the candidate.
+        The returned network may be worse than `nn` (non-elitist acceptance, restarts): the run loop keeps the best network found separately and reports it.
+     */
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        let variation = self.vary(&nn, context);
+        let mutatedEvaluation = self.getProblem().evaluate(&variation.nn, context);
+        self.adapt(&variation, mutatedEvaluation.score > originalScore);
+        MutationResult::elitist(nn, variation.nn, originalScore, mutatedEvaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes)
+    }
+}
+
+/*
+    A mutated copy of a network, before it's evaluated.
+ */
+pub struct Variation {
+    pub nn: NANN,
+    // Number of weights and biases changed
+    pub mutatedParameters: usize,
+    // Number of connections switched on or off, which isn't included in `mutatedParameters`
+    pub toggledConnections: usize,
+    // Step sizes used for each mutated parameter, for the algorithms that adapt them. Empty otherwise
+    pub stepSizes: Vec<f64>,
+    // Layer, row and column of each mutated weight, for the algorithms that adapt per-parameter step sizes. Empty otherwise.
+    // When the network uses biases, the bias of the same column was mutated too
+    pub positions: Vec<(usize, usize, usize)>
+}
+
+/*
+    Cell of a behaviour archive, and the best score of the networks whose behaviour fell in it.
+ */
+pub struct ArchiveCell {
+    // Index of the cell along every behaviour dimension
+    pub cell: Vec<usize>,
+    pub score: f64
+}
+
+impl ArchiveCell {
+    // Cell of a behaviour in [0, 1]^d, when each dimension is split into `cellsPerDimension` equal parts
+    pub fn cellOf(behaviour: &[f64], cellsPerDimension: usize) -> Vec<usize> {
+        behaviour.iter().map(|b| ((b * cellsPerDimension as f64).floor().max(0.0) as usize).min(cellsPerDimension - 1)).collect()
+    }
+}
+
+/*
+    Member of a Pareto front: the objectives it was ranked by, and its scalar score.
+ */
+pub struct ParetoPoint {
+    pub score: f64,
+    pub connections: usize,
+    // The problem's objectives, or the score alone for problems without separate objectives
+    pub objectives: Vec<(&'static str, f64)>
+}
+
+/*
+    Outcome of a single call to `mutate`: the network kept for the next generation, and how the candidate was created.
+ */
+pub struct MutationResult {
+    pub nn: NANN,
+    // Whether the mutated candidate replaced the original network
+    pub accepted: bool,
+    // Evaluation of the mutated candidate, which becomes the evaluation of the kept network if it was accepted
+    pub evaluation: Evaluation,
+    // Number of weights and biases changed in the candidate
+    pub mutatedParameters: usize,
+    // Number of connections switched on or off in the candidate
+    pub toggledConnections: usize,
+    // Step sizes used for each mutated parameter, for the algorithms that adapt them. Empty otherwise
+    pub stepSizes: Vec<f64>
+}
+
+impl MutationResult {
+    // Keeps the mutated network if it scores at least as well as the original one
+    pub fn elitist(nn: NANN, mutatedNN: NANN, originalScore: f64, mutatedEvaluation: Evaluation, mutatedParameters: usize, toggledConnections: usize, stepSizes: Vec<f64>) -> MutationResult {
+        let accepted = mutatedEvaluation.score >= originalScore;
+        MutationResult {
+            nn: if accepted { mutatedNN } else { nn },
+            accepted,
+            evaluation: mutatedEvaluation,
+            mutatedParameters,
+            toggledConnections,
+            stepSizes
+        }
+    }
+}
+
+/*
+    Draws the position of a weight to mutate, as the (1+1)NA family does: i is the layer, x and y the coordinates in that layer's matrix.
+    With probability `toggleProbability` the connection at that position is switched on or off instead, keeping its weight, and true is returned.
+    Otherwise the caller mutates the weight, and the bias of column y when the network uses biases.
+ */
+pub fn drawPosition(nn: &mut NANN, toggleProbability: f64, randGen: &mut impl Rng) -> ((usize, usize, usize), bool) {
+    let i = randGen.gen_range(0..nn.layers.len());
+    let x = randGen.gen_range(0..nn.layers[i].weights.nrows());
+    let y = randGen.gen_range(0..nn.layers[i].weights.ncols());
+    let toggled = toggleProbability > 0.0 && randGen.gen_bool(toggleProbability);
+    if toggled {
+        nn.layers[i].toggleConnection(x, y);
+    }
+    ((i, x, y), toggled)
 }
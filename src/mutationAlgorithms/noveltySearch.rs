@@ -0,0 +1,138 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{ArchiveCell, MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::utils::experimentContext::ExperimentContext;
+
+// Number of nearest behaviours the novelty of a network is averaged over
+const K_NEAREST: usize = 15;
+// The oldest behaviours are forgotten past this size, so a generation's cost doesn't grow with the run
+const MAX_ARCHIVE_SIZE: usize = 500;
+
+struct Individual {
+    nn: NANN,
+    score: f64,
+    behaviour: Vec<f64>
+}
+
+/*
+    Novelty search (Lehman & Stanley, 2011), using another mutation algorithm's variation as its mutation operator.
+    Every generation, each of the n parents is replaced by the n most novel of parents and offspring, ignoring their scores:
+    the novelty of a network is its mean behaviour distance to the 15 nearest parents, offspring and archived behaviours.
+    The most novel offspring of every generation is added to the archive. The first generation mutates the network of the first generation n times.
+    The grid of visited behaviours is kept with the best score in every cell, like a MAP-Elites archive.
+ */
+pub struct NoveltySearch {
+    operator: Box<dyn MutationAlgorithm>,
+    randGen: StdRng,
+    populationSize: usize,
+    population: Vec<Individual>,
+    archive: VecDeque<Vec<f64>>,
+    cellsPerDimension: usize,
+    visitedCells: BTreeMap<Vec<usize>, f64>
+}
+
+impl NoveltySearch {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, populationSize: usize, cellsPerDimension: usize, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(NoveltySearch {
+            operator,
+            randGen: StdRng::seed_from_u64(seed),
+            populationSize,
+            population: vec![],
+            archive: VecDeque::new(),
+            cellsPerDimension,
+            visitedCells: BTreeMap::new()
+        })
+    }
+
+    // Mean distance from `behaviour` to its nearest neighbours among `others` (which contains `behaviour` itself at `index`) and the archive
+    fn novelty(&self, index: usize, others: &[&Vec<f64>]) -> f64 {
+        let behaviour = others[index];
+        let distance = |other: &Vec<f64>| behaviour.iter().zip(other).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt();
+        let mut distances: Vec<f64> = others.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, other)| distance(other))
+            .chain(self.archive.iter().map(distance))
+            .collect();
+        distances.sort_by(|a, b| a.total_cmp(b));
+        distances.truncate(K_NEAREST);
+        if distances.is_empty() { 0.0 } else { distances.iter().sum::<f64>() / distances.len() as f64 }
+    }
+}
+
+impl fmt::Display for NoveltySearch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Novelty Search-{} {}", self.populationSize, self.operator)
+    }
+}
+
+impl MutationAlgorithm for NoveltySearch {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    fn reset(&mut self) {
+        self.population.clear();
+        self.archive.clear();
+        self.operator.reset();
+    }
+
+    fn archive(&self) -> Vec<ArchiveCell> {
+        self.visitedCells.iter().map(|(cell, score)| ArchiveCell { cell: cell.clone(), score: *score }).collect()
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        let mut candidate: Option<(NANN, Evaluation, usize, usize, Vec<f64>)> = None;
+        let mut offspring = Vec::with_capacity(self.populationSize);
+        for _ in 0..self.populationSize {
+            let (parent, parentScore) = if self.population.is_empty() {
+                (&nn, originalScore)
+            } else {
+                let index = self.randGen.gen_range(0..self.population.len());
+                (&self.population[index].nn, self.population[index].score)
+            };
+            let variation = self.operator.vary(parent, context);
+            let evaluation = self.operator.getProblem().evaluate(&variation.nn, context);
+            self.operator.adapt(&variation, evaluation.score > parentScore);
+
+            let cell = ArchiveCell::cellOf(&evaluation.behaviour, self.cellsPerDimension);
+            let best = self.visitedCells.entry(cell).or_insert(evaluation.score);
+            *best = best.max(evaluation.score);
+            offspring.push(Individual { nn: variation.nn.clone(), score: evaluation.score, behaviour: evaluation.behaviour.clone() });
+            if candidate.as_ref().map_or(true, |(_, best, _, _, _)| evaluation.score > best.score) {
+                candidate = Some((variation.nn, evaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes));
+            }
+        }
+
+        let offspringCount = offspring.len();
+        let mut pool: Vec<Individual> = offspring;
+        pool.append(&mut self.population);
+        let novelties: Vec<f64> = {
+            let behaviours: Vec<&Vec<f64>> = pool.iter().map(|individual| &individual.behaviour).collect();
+            (0..pool.len()).map(|i| self.novelty(i, &behaviours)).collect()
+        };
+        let mostNovelOffspring = (0..offspringCount).max_by(|a, b| novelties[*a].total_cmp(&novelties[*b])).unwrap();
+        self.archive.push_back(pool[mostNovelOffspring].behaviour.clone());
+        if self.archive.len() > MAX_ARCHIVE_SIZE {
+            self.archive.pop_front();
+        }
+
+        // Ties in novelty are broken by score
+        let mut ranked: Vec<(f64, Individual)> = novelties.into_iter().zip(pool).collect();
+        ranked.sort_by(|(noveltyA, a), (noveltyB, b)| noveltyB.total_cmp(noveltyA).then(b.score.total_cmp(&a.score)));
+        self.population = ranked.into_iter().take(self.populationSize).map(|(_, individual)| individual).collect();
+
+        let (mutatedNN, evaluation, mutatedParameters, toggledConnections, stepSizes) = candidate.unwrap();
+        MutationResult::elitist(nn, mutatedNN, originalScore, evaluation, mutatedParameters, toggledConnections, stepSizes)
+    }
+}
@@ -1,24 +1,30 @@
 use std::fmt;
 
-use crate::mutationAlgorithms::mutationAlgorithm::MutationAlgorithm;
+use crate::mutationAlgorithms::mutationAlgorithm::{drawPosition, MutationAlgorithm, Variation};
 use crate::problems::naProblem::NAProblem;
 use crate::nn::ann::NANN;
+use rand::{rngs::StdRng, SeedableRng};
+use crate::utils::experimentContext::ExperimentContext;
 use crate::utils::mathUtils;
 use rand::{Rng, distributions};
 use rand_distr::{Bernoulli, Uniform};
 
 pub struct OnePlusOneNA {
     problem: Box<dyn NAProblem>,
+    randGen: StdRng,
     resolutionParameter: f64,
-    numberOfNeurons: u32
+    numberOfNeurons: u32,
+    toggleProbability: f64
 }
 
 impl OnePlusOneNA {
-    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64) ->  Box<dyn MutationAlgorithm> {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, toggleProbability: f64, seed: u64) -> Box<dyn MutationAlgorithm> {
         Box::new(OnePlusOneNA {
             problem,
+            randGen: StdRng::seed_from_u64(seed),
             resolutionParameter,
-            numberOfNeurons: nn.layers.iter().fold(0, |total, l| total + l.getBiases().len()) as u32
+            numberOfNeurons: nn.layers.iter().fold(0, |total, l| total + l.getBiases().len()) as u32,
+            toggleProbability
         })
     }
 }
@@ -34,33 +40,39 @@ impl MutationAlgorithm for OnePlusOneNA{
         &self.problem
     }
     
-    fn mutate(&mut self, nn: NANN, originalScore: f64) -> NANN {
-        let mut randGen = rand::thread_rng();
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        let randGen = &mut self.randGen;
         let mut mutatedNN = nn.clone();
         let bernoulli = Bernoulli::from_ratio(1, self.numberOfNeurons).unwrap();
         let uniform = Uniform::new_inclusive(-1.0, 1.0);
 
+        let mut mutatedParameters = 0;
+        let mut toggledConnections = 0;
+
         // At least one parameter is always mutated
         loop {
-            // Draw what parameter is mutated. i is the layer, x and y correspond to the coordinates in that layer's matrix
-            let i = randGen.gen_range(0..mutatedNN.layers.len());
-            let x = randGen.gen_range(0..mutatedNN.layers[i].weights.nrows());
-            let y = randGen.gen_range(0..mutatedNN.layers[i].weights.ncols());
-            mutatedNN.layers[i].weights[(x, y)] += (mathUtils::harmonicDistribution() / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
-            if mutatedNN.isUsingBias() {
-                mutatedNN.layers[i].biases[(y)] += (mathUtils::harmonicDistribution() / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
+            // Draw what parameter is mutated, unless its connection is toggled instead
+            let ((i, x, y), toggled) = drawPosition(&mut mutatedNN, self.toggleProbability, randGen);
+            if toggled {
+                toggledConnections += 1;
+            } else {
+                mutatedNN.layers[i].weights[(x, y)] += (mathUtils::harmonicDistribution(context.resolution, randGen) / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
+                if mutatedNN.isUsingBias() {
+                    mutatedNN.layers[i].biases[(y)] += (mathUtils::harmonicDistribution(context.resolution, randGen) / self.resolutionParameter) * randGen.sample::<f64, Uniform<f64>>(uniform).signum();
+                }
+                mutatedParameters += if mutatedNN.isUsingBias() { 2 } else { 1 };
             }
-            
             if !randGen.sample(bernoulli) {
                 break;
             }
         }
 
-        let (_, mutatedScore, _) = self.problem.evaluate(&mutatedNN);
-        if mutatedScore >= originalScore {
-            mutatedNN
-        } else {
-            nn
+        Variation {
+            nn: mutatedNN,
+            mutatedParameters,
+            toggledConnections,
+            stepSizes: vec![],
+            positions: vec![]
         }
     }
 }
\ No newline at end of file
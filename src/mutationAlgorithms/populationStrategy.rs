@@ -0,0 +1,190 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::{Evaluation, NAProblem};
+use crate::utils::experimentContext::ExperimentContext;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Selection {
+    // The next parents are chosen among the current parents and their offspring
+    Plus,
+    // The next parents are chosen among the offspring only
+    Comma
+}
+
+/*
+    Population sizes and selection of an evolution strategy: μ parents create λ offspring every generation.
+    Written as "(μ+λ)" or "(μ,λ)".
+ */
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Strategy {
+    pub mu: usize,
+    pub lambda: usize,
+    pub selection: Selection
+}
+
+impl Strategy {
+    // The single parent, single offspring scheme every mutation algorithm runs on its own
+    pub fn onePlusOne() -> Strategy {
+        Strategy {
+            mu: 1,
+            lambda: 1,
+            selection: Selection::Plus
+        }
+    }
+
+    pub fn isOnePlusOne(&self) -> bool {
+        *self == Strategy::onePlusOne()
+    }
+
+    // Parses "(μ+λ)" or "(μ,λ)", with or without the parentheses
+    pub fn fromName(name: &str) -> Result<Strategy, String> {
+        let text = name.trim();
+        let text = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')).unwrap_or(text);
+        let (selection, separator) = if text.contains('+') { (Selection::Plus, '+') } else { (Selection::Comma, ',') };
+        let sizes: Vec<&str> = text.split(separator).collect();
+        let (mu, lambda) = match sizes.as_slice() {
+            [mu, lambda] => (mu.trim().parse::<usize>(), lambda.trim().parse::<usize>()),
+            _ => return Err(format!("Invalid strategy: \"{}\", expected e.g. \"(5+10)\" or \"(1,10)\"", name))
+        };
+        match (mu, lambda) {
+            (Ok(mu), Ok(lambda)) => Strategy { mu, lambda, selection }.validate(),
+            _ => Err(format!("Invalid strategy: \"{}\", expected e.g. \"(5+10)\" or \"(1,10)\"", name))
+        }
+    }
+
+    pub fn validate(self) -> Result<Strategy, String> {
+        if self.mu == 0 || self.lambda == 0 {
+            return Err(format!("{}: there must be at least one parent and one offspring", self));
+        }
+        if self.selection == Selection::Comma && self.lambda < self.mu {
+            return Err(format!("{}: comma selection needs at least as many offspring as parents", self));
+        }
+        Ok(self)
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.selection {
+            Selection::Plus => "+",
+            Selection::Comma => ","
+        };
+        write!(f, "({}{}{})", self.mu, separator, self.lambda)
+    }
+}
+
+struct Individual {
+    id: u64,
+    nn: NANN,
+    score: f64
+}
+
+/*
+    Evolution strategy with a population of parents, using another mutation algorithm's variation as its mutation operator.
+    Every generation, λ offspring are created from parents drawn uniformly at random, and the best μ individuals become the next parents.
+    Self-adaptive operators adapt their step sizes whenever an offspring is evaluated, comparing it with its own parent.
+    The population starts from the network of the first generation alone, and is filled up to μ by the first generations' offspring.
+    Each call to `mutate` is a whole generation, and returns the best parent. It counts as accepted whenever the best parent changes.
+ */
+pub struct PopulationStrategy {
+    operator: Box<dyn MutationAlgorithm>,
+    strategy: Strategy,
+    // Sorted from best to worst score
+    population: Vec<Individual>,
+    nextId: u64,
+    randGen: StdRng
+}
+
+impl PopulationStrategy {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, strategy: Strategy, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(PopulationStrategy {
+            operator,
+            strategy,
+            population: vec![],
+            nextId: 0,
+            randGen: StdRng::seed_from_u64(seed)
+        })
+    }
+
+    fn newIndividual(&mut self, nn: NANN, score: f64) -> Individual {
+        self.nextId += 1;
+        Individual {
+            id: self.nextId,
+            nn,
+            score
+        }
+    }
+}
+
+impl fmt::Display for PopulationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-ES {}", self.strategy, self.operator)
+    }
+}
+
+impl MutationAlgorithm for PopulationStrategy {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    // The population is rebuilt from the next network
+    fn reset(&mut self) {
+        self.population.clear();
+        self.operator.reset();
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        if self.population.is_empty() {
+            let individual = self.newIndividual(nn, originalScore);
+            self.population.push(individual);
+        }
+        let previousBest = self.population[0].id;
+
+        let mut candidate: Option<(Evaluation, usize, usize, Vec<f64>)> = None;
+        let mut offspring = Vec::with_capacity(self.strategy.lambda);
+        for _ in 0..self.strategy.lambda {
+            let parent = self.randGen.gen_range(0..self.population.len());
+            let variation = self.operator.vary(&self.population[parent].nn, context);
+            let evaluation = self.operator.getProblem().evaluate(&variation.nn, context);
+            self.operator.adapt(&variation, evaluation.score > self.population[parent].score);
+
+            let child = self.newIndividual(variation.nn, evaluation.score);
+            if candidate.as_ref().map_or(true, |(best, _, _, _)| evaluation.score > best.score) {
+                candidate = Some((evaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes));
+            }
+            offspring.push(child);
+        }
+
+        // Offspring come first so they win ties against their parents, as in the elitist (1+1) schemes.
+        // A new best individual is therefore always the candidate
+        if self.strategy.selection == Selection::Plus {
+            offspring.append(&mut self.population);
+        }
+        offspring.sort_by(|a, b| b.score.total_cmp(&a.score));
+        offspring.truncate(self.strategy.mu);
+        self.population = offspring;
+
+        let (evaluation, mutatedParameters, toggledConnections, stepSizes) = candidate.unwrap();
+        let best = &self.population[0];
+        MutationResult {
+            nn: best.nn.clone(),
+            accepted: best.id != previousBest,
+            evaluation,
+            mutatedParameters,
+            toggledConnections,
+            stepSizes
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::NAProblem;
+use crate::utils::experimentContext::ExperimentContext;
+
+/*
+    Random local search: every generation, exactly one parameter chosen uniformly at random moves by 1/R in either direction,
+    and the change is kept if the score is at least as good.
+    After `restartPatience` generations without a strict improvement, the search restarts from a new random network,
+    which replaces the current one whatever its score.
+ */
+pub struct RandomLocalSearch {
+    problem: Box<dyn NAProblem>,
+    randGen: StdRng,
+    resolutionParameter: f64,
+    restartPatience: u64,
+    stalledGenerations: u64,
+    restarts: u32
+}
+
+impl RandomLocalSearch {
+    pub fn new(problem: Box<dyn NAProblem>, resolutionParameter: f64, restartPatience: u64, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(RandomLocalSearch {
+            problem,
+            randGen: StdRng::seed_from_u64(seed),
+            resolutionParameter,
+            restartPatience,
+            stalledGenerations: 0,
+            restarts: 0
+        })
+    }
+}
+
+impl fmt::Display for RandomLocalSearch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Random Local Search")
+    }
+}
+
+impl MutationAlgorithm for RandomLocalSearch {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        &self.problem
+    }
+
+    fn vary(&mut self, nn: &NANN, _context: &ExperimentContext) -> Variation {
+        let mut parameters = nn.parameters();
+        let i = self.randGen.gen_range(0..parameters.len());
+        let direction = if self.randGen.gen_bool(0.5) { 1.0 } else { -1.0 };
+        parameters[i] += direction / self.resolutionParameter;
+        Variation {
+            nn: nn.withParameters(&parameters),
+            mutatedParameters: 1,
+            toggledConnections: 0,
+            stepSizes: vec![],
+            positions: vec![]
+        }
+    }
+
+    fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        if self.stalledGenerations >= self.restartPatience {
+            self.stalledGenerations = 0;
+            self.restarts += 1;
+            let restarted = nn.reinitialised(self.resolutionParameter, &mut self.randGen);
+            let evaluation = self.problem.evaluate(&restarted, context);
+            let mutatedParameters = restarted.parameters().len();
+            return MutationResult {
+                nn: restarted,
+                accepted: true,
+                evaluation,
+                mutatedParameters,
+                toggledConnections: 0,
+                stepSizes: vec![]
+            };
+        }
+        let variation = self.vary(&nn, context);
+        let mutatedEvaluation = self.problem.evaluate(&variation.nn, context);
+        if mutatedEvaluation.score > originalScore {
+            self.stalledGenerations = 0;
+        } else {
+            self.stalledGenerations += 1;
+        }
+        MutationResult::elitist(nn, variation.nn, originalScore, mutatedEvaluation, variation.mutatedParameters, variation.toggledConnections, variation.stepSizes)
+    }
+}
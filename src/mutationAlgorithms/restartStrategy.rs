@@ -0,0 +1,145 @@
+use std::fmt;
+
+use druid::Data;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::Uniform;
+use serde::Deserialize;
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::NAProblem;
+use crate::utils::experimentContext::ExperimentContext;
+
+// What a restart starts from
+#[derive(Data, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RestartMode {
+    // A new random network, initialised like the first network of a run
+    Reinitialise,
+    // The current network, with every parameter moved by a uniformly random value in [-1, 1], rounded to the 1/R grid
+    Perturb
+}
+
+impl RestartMode {
+    // Every restart mode, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, RestartMode)> {
+        vec![
+            ("Reinitialise", RestartMode::Reinitialise),
+            ("Perturb", RestartMode::Perturb),
+        ]
+    }
+
+    // Looks up a restart mode by its name, ignoring case
+    pub fn fromName(name: &str) -> Option<RestartMode> {
+        RestartMode::all().into_iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(name))
+            .map(|(_, mode)| mode)
+    }
+}
+
+impl fmt::Display for RestartMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = RestartMode::all().into_iter().find(|(_, mode)| mode == self).map_or("", |(label, _)| label);
+        write!(f, "{}", name)
+    }
+}
+
+/*
+    Restarts another mutation algorithm once it goes `patience` generations without beating the best score since its last restart.
+    The restarted network replaces the current one whatever its score, and the algorithm is reset, e.g. its step sizes or population.
+    As in IPOP, the patience doubles after every restart, so later restarts get longer runs.
+ */
+pub struct RestartStrategy {
+    operator: Box<dyn MutationAlgorithm>,
+    randGen: StdRng,
+    mode: RestartMode,
+    resolutionParameter: f64,
+    patience: u64,
+    // Best score since the last restart
+    bestScore: f64,
+    stalledGenerations: u64,
+    restarts: u32
+}
+
+impl RestartStrategy {
+    pub fn new(operator: Box<dyn MutationAlgorithm>, mode: RestartMode, resolutionParameter: f64, patience: u64, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(RestartStrategy {
+            operator,
+            randGen: StdRng::seed_from_u64(seed),
+            mode,
+            resolutionParameter,
+            patience,
+            bestScore: f64::NEG_INFINITY,
+            stalledGenerations: 0,
+            restarts: 0
+        })
+    }
+
+    fn restartedNetwork(&mut self, nn: &NANN) -> NANN {
+        match self.mode {
+            RestartMode::Reinitialise => nn.reinitialised(self.resolutionParameter, &mut self.randGen),
+            RestartMode::Perturb => {
+                let uniform = Uniform::new_inclusive(-1.0, 1.0);
+                let r = self.resolutionParameter;
+                let parameters = nn.parameters().mapv(|p| ((p + self.randGen.sample(uniform)) * r).round() / r);
+                nn.withParameters(&parameters)
+            }
+        }
+    }
+}
+
+impl fmt::Display for RestartStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} with {} Restarts", self.operator, self.mode)
+    }
+}
+
+impl MutationAlgorithm for RestartStrategy {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.operator.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.operator.vary(nn, context)
+    }
+
+    fn adapt(&mut self, variation: &Variation, improved: bool) {
+        self.operator.adapt(variation, improved)
+    }
+
+    fn reset(&mut self) {
+        self.operator.reset();
+        self.bestScore = f64::NEG_INFINITY;
+        self.stalledGenerations = 0;
+    }
+
+    fn restarts(&self) -> u32 {
+        self.restarts + self.operator.restarts()
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        if originalScore > self.bestScore {
+            self.bestScore = originalScore;
+            self.stalledGenerations = 0;
+        }
+        if self.stalledGenerations < self.patience {
+            self.stalledGenerations += 1;
+            return self.operator.mutate(nn, originalScore, context);
+        }
+
+        self.restarts += 1;
+        self.patience *= 2;
+        self.reset();
+        let restarted = self.restartedNetwork(&nn);
+        let evaluation = self.getProblem().evaluate(&restarted, context);
+        let mutatedParameters = restarted.parameters().len();
+        MutationResult {
+            nn: restarted,
+            accepted: true,
+            evaluation,
+            mutatedParameters,
+            toggledConnections: 0,
+            stepSizes: vec![]
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use druid::Data;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult, Variation};
+use crate::mutationAlgorithms::onePlusOneNA::OnePlusOneNA;
+use crate::nn::ann::NANN;
+use crate::problems::naProblem::NAProblem;
+use crate::utils::experimentContext::ExperimentContext;
+use crate::utils::mathUtils;
+
+// How the temperature of simulated annealing decreases with the generation t
+#[derive(Data, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Cooling {
+    // T0 * rate^t
+    Exponential,
+    // T0 * (1 - t / maximum generations), reaching 0 at the end of the generation budget
+    Linear,
+    // T0 / (1 + ln(1 + t))
+    Logarithmic
+}
+
+impl Cooling {
+    // Every cooling schedule, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, Cooling)> {
+        vec![
+            ("Exponential", Cooling::Exponential),
+            ("Linear", Cooling::Linear),
+            ("Logarithmic", Cooling::Logarithmic),
+        ]
+    }
+
+    // Looks up a cooling schedule by its name, ignoring case
+    pub fn fromName(name: &str) -> Option<Cooling> {
+        Cooling::all().into_iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(name))
+            .map(|(_, schedule)| schedule)
+    }
+}
+
+impl fmt::Display for Cooling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Cooling::all().into_iter().find(|(_, schedule)| schedule == self).map_or("", |(label, _)| label);
+        write!(f, "{}", name)
+    }
+}
+
+// Temperature of simulated annealing over a run: how it cools, from what temperature, and the rate of exponential cooling
+#[derive(Debug, Clone, Copy)]
+pub struct CoolingSchedule {
+    pub cooling: Cooling,
+    pub initialTemperature: f64,
+    pub rate: f64
+}
+
+impl CoolingSchedule {
+    // Temperature after `generation` generations
+    pub fn temperature(&self, generation: u64, context: &ExperimentContext) -> f64 {
+        let t = generation as f64;
+        match self.cooling {
+            Cooling::Exponential => self.initialTemperature * self.rate.powf(t),
+            Cooling::Linear => {
+                // ExperimentConfig::validate rejects linear cooling under budgets without a generation limit
+                let maxGenerations = context.budget.maxGenerations.expect("Linear cooling needs a generation limit");
+                self.initialTemperature * (1.0 - t / maxGenerations as f64).max(0.0)
+            },
+            Cooling::Logarithmic => self.initialTemperature / (1.0 + (1.0 + t).ln())
+        }
+    }
+}
+
+/*
+    Non-elitist simulated annealing, using the (1+1)NA mutation to create neighbours.
+    Neighbours at least as good are always accepted, worse ones with probability exp((mutated score - original score) / T).
+ */
+pub struct SimulatedAnnealing {
+    neighbourhood: Box<dyn MutationAlgorithm>,
+    randGen: StdRng,
+    schedule: CoolingSchedule,
+    generation: u64
+}
+
+impl SimulatedAnnealing {
+    pub fn new(nn: &NANN, problem: Box<dyn NAProblem>, resolutionParameter: f64, schedule: CoolingSchedule, toggleProbability: f64, seed: u64) -> Box<dyn MutationAlgorithm> {
+        Box::new(SimulatedAnnealing {
+            neighbourhood: OnePlusOneNA::new(nn, problem, resolutionParameter, toggleProbability, seed),
+            randGen: StdRng::seed_from_u64(mathUtils::deriveSeed(seed, 0)),
+            schedule,
+            generation: 0
+        })
+    }
+}
+
+impl fmt::Display for SimulatedAnnealing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Simulated Annealing {} Cooling", self.schedule.cooling)
+    }
+}
+
+impl MutationAlgorithm for SimulatedAnnealing {
+    fn getProblem(&self) -> &Box<dyn NAProblem> {
+        self.neighbourhood.getProblem()
+    }
+
+    fn vary(&mut self, nn: &NANN, context: &ExperimentContext) -> Variation {
+        self.neighbourhood.vary(nn, context)
+    }
+
+    // Reheats to the initial temperature
+    fn reset(&mut self) {
+        self.generation = 0;
+    }
+
+    fn mutate(&mut self, nn: NANN, originalScore: f64, context: &ExperimentContext) -> MutationResult {
+        let variation = self.vary(&nn, context);
+        let mutatedEvaluation = self.getProblem().evaluate(&variation.nn, context);
+        let temperature = self.schedule.temperature(self.generation, context);
+        self.generation += 1;
+
+        let difference = mutatedEvaluation.score - originalScore;
+        let accepted = difference >= 0.0 || (temperature > 0.0 && self.randGen.gen::<f64>() < (difference / temperature).exp());
+        MutationResult {
+            nn: if accepted { variation.nn } else { nn },
+            accepted,
+            evaluation: mutatedEvaluation,
+            mutatedParameters: variation.mutatedParameters,
+            toggledConnections: variation.toggledConnections,
+            stepSizes: variation.stepSizes
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use druid::Data;
+use ndarray::Array1;
+
+// Activation function applied element-wise to a layer's outputs
+#[derive(Data, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Activation {
+    Identity,
+    // 1 for non-negative inputs and -1 otherwise
+    Sign,
+    ReLU,
+    Tanh,
+    Sigmoid,
+    // 1 for non-negative inputs and 0 otherwise, the threshold neuron used by the (1+1)NA
+    BinaryThreshold
+}
+
+impl Activation {
+    // Every selectable activation, paired with the name shown to the user
+    pub fn all() -> Vec<(&'static str, Activation)> {
+        vec![
+            ("Identity", Activation::Identity),
+            ("Sign", Activation::Sign),
+            ("ReLU", Activation::ReLU),
+            ("Tanh", Activation::Tanh),
+            ("Sigmoid", Activation::Sigmoid),
+            ("Binary Threshold", Activation::BinaryThreshold),
+        ]
+    }
+
+    // Looks up an activation either by its display name or its variant name, ignoring case
+    pub fn fromName(name: &str) -> Option<Activation> {
+        Activation::all().into_iter()
+            .find(|(label, activation)| label.eq_ignore_ascii_case(name) || format!("{:?}", activation).eq_ignore_ascii_case(name))
+            .map(|(_, activation)| activation)
+    }
+
+    pub fn evaluate(&self, x: f64) -> f64 {
+        match self {
+            Activation::Identity => x,
+            Activation::Sign => if x >= 0.0 { 1.0 } else { -1.0 },
+            Activation::ReLU => x.max(0.0),
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::BinaryThreshold => if x >= 0.0 { 1.0 } else { 0.0 },
+        }
+    }
+
+    pub fn apply(&self, mut outputs: Array1<f64>) -> Array1<f64> {
+        if *self != Activation::Identity {
+            outputs.mapv_inplace(|x| self.evaluate(x));
+        }
+        outputs
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Activation::all().into_iter().find(|(_, activation)| activation == self).map_or("", |(label, _)| label);
+        write!(f, "{}", name)
+    }
+}
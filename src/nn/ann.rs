@@ -1,33 +1,57 @@
+use std::borrow::Cow;
 use std::f64::consts;
+use std::time::Instant;
 
 use ndarray::{Array2, Array1, Array};
 use rand_distr::Uniform;
-use crate::{problems::sphereContinuousProblem::SphereContinuousNAProblem, mutationAlgorithms::mutationAlgorithm::MutationAlgorithm, utils::mathUtils};
+use crate::{problems::{countingProblem::EvaluationCounter, naProblem::Evaluation, sphereContinuousProblem::SphereContinuousNAProblem}, mutationAlgorithms::mutationAlgorithm::{MutationAlgorithm, MutationResult}, utils::{mathUtils, experimentContext::ExperimentContext}};
 use ndarray_rand::RandomExt;
+use rand::rngs::StdRng;
+
+use super::activation::Activation;
 
 #[derive(Debug, Clone)]
 pub struct Layer {
     pub weights: Array2<f64>,
     pub biases: Array1<f64>,
-    activationFunction: fn(Array2<f64>) -> Array2<f64>
+    // Whether each weight connects its input to its output. Disabled connections are ignored by the forward pass
+    pub mask: Array2<bool>,
+    pub activation: Activation
 }
 
 impl Layer {
     // Creates a layer and initializes it's parameter's values.
-    pub fn new(dimInput: usize, dimOutput: usize, activationFunction: fn(Array2<f64>) -> Array2<f64> , resolutionParameter: f64, usingBias: bool) -> Layer {
-        let mut weights = Array2::<f64>::random((dimInput, dimOutput), Uniform::new_inclusive(-0.1 * resolutionParameter, 0.1 * resolutionParameter));
+    pub fn new(dimInput: usize, dimOutput: usize, activation: Activation, resolutionParameter: f64, usingBias: bool, randGen: &mut StdRng) -> Layer {
+        let mut weights = Array2::<f64>::random_using((dimInput, dimOutput), Uniform::new_inclusive(-0.1 * resolutionParameter, 0.1 * resolutionParameter), randGen);
         weights.mapv_inplace(|w| w.round()/resolutionParameter);
         let mut biases: Array1<f64>;
         if usingBias {
-            biases = Array::random(dimOutput, Uniform::new_inclusive(-0.1 * resolutionParameter, 0.1 * resolutionParameter));
+            biases = Array::random_using(dimOutput, Uniform::new_inclusive(-0.1 * resolutionParameter, 0.1 * resolutionParameter), randGen);
             biases.mapv_inplace(|b| b.round()/resolutionParameter);
         } else {
             biases = Array::zeros(dimOutput);
         }
         Layer {
+            mask: Array2::from_elem(weights.raw_dim(), true),
             weights,
             biases,
-            activationFunction
+            activation
+        }
+    }
+
+    // Switches the connection from input x to output y off if it's on, and on otherwise. Its weight is kept for when it's switched back on
+    pub fn toggleConnection(&mut self, x: usize, y: usize) {
+        self.mask[(x, y)] = !self.mask[(x, y)];
+    }
+
+    // Weights with the disabled connections set to 0
+    pub fn effectiveWeights(&self) -> Cow<'_, Array2<f64>> {
+        if self.mask.iter().all(|enabled| *enabled) {
+            Cow::Borrowed(&self.weights)
+        } else {
+            let mut weights = self.weights.clone();
+            weights.zip_mut_with(&self.mask, |w, enabled| if !*enabled { *w = 0.0 });
+            Cow::Owned(weights)
         }
     }
 
@@ -35,16 +59,17 @@ impl Layer {
         &self.biases
     }
 
-    // Single layer forward pass
+    // Single layer forward pass, including the layer's activation
     pub fn forward(&self, inputs: Array1<f64>) -> Array1<f64> {
         let mut outputs : Array1<f64>;
-        match (inputs.dim(), self.weights.dim()) {
-            (x, (y, _)) if x == y => outputs = inputs.dot(&self.weights),
-            (x, (_, y)) if x == y => outputs = self.weights.dot(&inputs),
+        let weights = self.effectiveWeights();
+        match (inputs.dim(), weights.dim()) {
+            (x, (y, _)) if x == y => outputs = inputs.dot(weights.as_ref()),
+            (x, (_, y)) if x == y => outputs = weights.dot(&inputs),
             (x, (m, n)) => panic!("Matrices cannot be multiplied: ({x}) and ({m}, {n})"),
         }
         outputs = outputs + self.getBiases();
-        return outputs;
+        return self.activation.apply(outputs);
     }
 }
 
@@ -55,11 +80,14 @@ pub struct NANN {
 }
 
 impl NANN {
-    // Creates a neural network with the provided specifications. Layer sizes are provided as tuples in the form (inputSize, outputSize)
-    pub fn new(layerSizes: Vec<(usize, usize)>, activationFunction: fn(Array2<f64>) -> Array2<f64>, resolutionParameter: f64, usingBias: bool) -> NANN {
-        let layers = layerSizes.iter().map(
-            |(inputSize, outputSize)|
-                Layer::new(*inputSize, *outputSize, activationFunction,  resolutionParameter, usingBias)
+    // Creates a neural network with the provided specifications. Layer sizes are provided as tuples in the form (inputSize, outputSize),
+    // and each layer uses the activation at the same position in `activations`
+    pub fn new(layerSizes: Vec<(usize, usize)>, activations: &[Activation], resolutionParameter: f64, usingBias: bool, randGen: &mut StdRng) -> NANN {
+        assert_eq!(layerSizes.len(), activations.len(), "Every layer needs an activation");
+        NANN::validateLayerSizes(&layerSizes).expect("Invalid network topology");
+        let layers = layerSizes.iter().zip(activations).map(
+            |((inputSize, outputSize), activation)|
+                Layer::new(*inputSize, *outputSize, *activation, resolutionParameter, usingBias, randGen)
         ).collect::<Vec<Layer>>();
         NANN {
             layers,
@@ -67,10 +95,80 @@ impl NANN {
         }
     }
 
+    // Assembles a network from existing layers, e.g. ones read from a file
+    pub fn fromLayers(layers: Vec<Layer>, usingBias: bool) -> Result<NANN, String> {
+        let layerSizes: Vec<(usize, usize)> = layers.iter().map(|layer| layer.weights.dim()).collect();
+        NANN::validateLayerSizes(&layerSizes)?;
+        if let Some(index) = layers.iter().position(|layer| layer.biases.len() != layer.weights.ncols()) {
+            return Err(format!("Layer {} has {} biases for {} outputs", index + 1, layers[index].biases.len(), layers[index].weights.ncols()));
+        }
+        if let Some(index) = layers.iter().position(|layer| layer.mask.dim() != layer.weights.dim()) {
+            return Err(format!("Layer {} has a connection mask of shape {:?} for weights of shape {:?}", index + 1, layers[index].mask.dim(), layers[index].weights.dim()));
+        }
+        Ok(NANN {
+            layers,
+            usingBias
+        })
+    }
+
+    // (inputSize, outputSize) of every layer
+    pub fn layerSizes(&self) -> Vec<(usize, usize)> {
+        self.layers.iter().map(|layer| layer.weights.dim()).collect()
+    }
+
+    // Checks that the network has at least one layer, no layer is empty and each layer's input size matches the previous layer's output size
+    pub fn validateLayerSizes(layerSizes: &[(usize, usize)]) -> Result<(), String> {
+        if layerSizes.is_empty() {
+            return Err("The network needs at least one layer".to_string());
+        }
+        if let Some((inputSize, outputSize)) = layerSizes.iter().find(|(inputSize, outputSize)| *inputSize == 0 || *outputSize == 0) {
+            return Err(format!("Layers cannot be empty: ({}, {})", inputSize, outputSize));
+        }
+        for (index, pair) in layerSizes.windows(2).enumerate() {
+            if pair[0].1 != pair[1].0 {
+                return Err(format!("Layer {} outputs {} values but layer {} takes {} inputs", index + 1, pair[0].1, index + 2, pair[1].0));
+            }
+        }
+        Ok(())
+    }
+
+    // New random network with the same layer sizes, activations and bias flag, initialised like the first network of a run
+    pub fn reinitialised(&self, resolutionParameter: f64, randGen: &mut StdRng) -> NANN {
+        let activations: Vec<Activation> = self.layers.iter().map(|layer| layer.activation).collect();
+        NANN::new(self.layerSizes(), &activations, resolutionParameter, self.usingBias, randGen)
+    }
+
+    // Number of weights that connect two neurons, i.e. are enabled in the mask and not 0
+    pub fn connectionCount(&self) -> usize {
+        self.layers.iter().map(|layer| layer.effectiveWeights().iter().filter(|w| **w != 0.0).count()).sum()
+    }
+
     pub fn isUsingBias(&self) -> bool {
         self.usingBias
     }
 
+    // Every parameter of the network as a single vector: each layer's weights (row-major), followed by its biases if the network uses them
+    pub fn parameters(&self) -> Array1<f64> {
+        self.layers.iter().flat_map(|layer| {
+            let biases = if self.usingBias { layer.biases.to_vec() } else { vec![] };
+            layer.weights.iter().copied().chain(biases).collect::<Vec<f64>>()
+        }).collect()
+    }
+
+    // Copy of the network with its parameters replaced, in the order given by `parameters`
+    pub fn withParameters(&self, parameters: &Array1<f64>) -> NANN {
+        let mut nn = self.clone();
+        let mut values = parameters.iter().copied();
+        for layer in nn.layers.iter_mut() {
+            layer.weights.iter_mut().for_each(|w| *w = values.next().expect("Not enough parameters for the network"));
+            if self.usingBias {
+                layer.biases.iter_mut().for_each(|b| *b = values.next().expect("Not enough parameters for the network"));
+            }
+        }
+        assert!(values.next().is_none(), "Too many parameters for the network");
+        nn
+    }
+
     pub fn forward(self, inputs: Array2<f64>) -> Array2<f64> {
         inputs.rows().into_iter().fold(Array2::from_elem((0, self.layers[self.layers.len()-1].weights.dim().1), 15.0), |mut acc, i| {
             acc.push_row(
@@ -84,25 +182,170 @@ impl NANN {
     
 }
 
-pub fn run(mut nn: NANN, mut mutationAlgorithm:  Box<dyn MutationAlgorithm>) -> (i32, i32, f64, Array2<f64>) {
+/*
+    Receives progress notifications from `run`. Every method does nothing by default.
+ */
+pub trait RunObserver {
+    // Called with the best score so far on the first generation and whenever it improves
+    fn onImprovement(&mut self, _generation: i32, _bestScore: f64) {}
+
+    // Called whenever the mutation algorithm accepts a mutated network
+    fn onAcceptedMutation(&mut self, _generation: i32, _mutation: &MutationResult) {}
+
+    // Called once the run is over, with the mutation algorithm in its final state
+    fn onFinished(&mut self, _mutationAlgorithm: &dyn MutationAlgorithm) {}
+}
+
+/*
+    Evolves `nn` until the problem is solved, the context's budget is exhausted or the run is cancelled, notifying `observer` along the way.
+    `evaluations` counts the evaluations of the mutation algorithm's problem, for evaluation budgets.
+    The current network is only evaluated once: afterwards its evaluation is carried forward, and replaced by the candidate's when a mutation is accepted.
+    Returns the final generation, the generation the best score was found in, the best network's evaluation, the best network
+    and the number of times the mutation algorithm restarted.
+ */
+pub fn run(mut nn: NANN, mut mutationAlgorithm:  Box<dyn MutationAlgorithm>, context: &ExperimentContext, evaluations: &EvaluationCounter, observer: &mut dyn RunObserver) -> (i32, i32, Evaluation, NANN, u32) {
+    let start = Instant::now();
     let mut i = 1;
-    let mut success;
-    let mut score = 0.0;
-    let mut solution = Array2::zeros([1usize, 1usize]);
+    // Evaluation of `nn` in the previous generation, and of the accepted candidate that replaced it, if any
+    let mut lastEvaluation: Option<Evaluation> = None;
+    let mut acceptedEvaluation: Option<Evaluation> = None;
     let mut maxScoreGeneration = 0;
-    let mut maxScore = 0.0;
-    while i as f64 <= unsafe { 100.0 * crate::R * crate::R.log2() } {
-        (success, score, solution) = mutationAlgorithm.getProblem().evaluate(&nn);
+    // Starts below any score, so the first generation always sets the best network and its evaluation
+    let mut maxScore = f64::NEG_INFINITY;
+    let mut bestNN = nn.clone();
+    // Evaluation of `bestNN`, which differs from the last one when the algorithm accepts worse networks
+    let mut bestEvaluation: Option<Evaluation> = None;
+    while !context.budget.isExhausted((i - 1) as u64, evaluations.get(), start.elapsed()) && !context.isCancelled() {
+        let evaluation = match acceptedEvaluation.take().or(lastEvaluation.take()) {
+            Some(evaluation) => evaluation,
+            None => mutationAlgorithm.getProblem().evaluate(&nn, context)
+        };
+        let score = evaluation.score;
         if score > maxScore {
             maxScore = score;
             maxScoreGeneration = i;
+            bestNN = nn.clone();
+            bestEvaluation = Some(evaluation.clone());
         }
-        if success {
-            return (i, maxScoreGeneration, score, solution);
+        if i == 1 || maxScoreGeneration == i {
+            observer.onImprovement(i, maxScore);
+        }
+        if evaluation.success {
+            observer.onFinished(mutationAlgorithm.as_ref());
+            return (i, maxScoreGeneration, evaluation, bestNN, mutationAlgorithm.restarts());
         } else {
-            nn = mutationAlgorithm.mutate(nn.clone(), score);
+            let mutation = mutationAlgorithm.mutate(nn.clone(), score, context);
+            if mutation.accepted {
+                observer.onAcceptedMutation(i, &mutation);
+                acceptedEvaluation = Some(mutation.evaluation);
+            }
+            nn = mutation.nn;
         }
+        lastEvaluation = Some(evaluation);
         i += 1;
     }
-    return (i, maxScoreGeneration, score, solution);
-}
\ No newline at end of file
+    // Only empty if the run was cancelled before its first generation
+    let evaluation = bestEvaluation.unwrap_or_else(|| mutationAlgorithm.getProblem().evaluate(&nn, context));
+    observer.onFinished(mutationAlgorithm.as_ref());
+    return (i, maxScoreGeneration, evaluation, bestNN, mutationAlgorithm.restarts());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::{self, AlgorithmEnum, ExperimentConfig, ProblemEnum};
+    use crate::problems::countingProblem::CountingProblem;
+
+    // Records the parameters of every accepted network, and the generation it was accepted in
+    #[derive(Default)]
+    struct AcceptedRecorder {
+        accepted: Vec<(i32, Vec<f64>)>
+    }
+
+    impl RunObserver for AcceptedRecorder {
+        fn onAcceptedMutation(&mut self, generation: i32, mutation: &MutationResult) {
+            self.accepted.push((generation, parameters(&mutation.nn)));
+        }
+    }
+
+    fn parameters(nn: &NANN) -> Vec<f64> {
+        nn.layers.iter().flat_map(|layer| layer.weights.iter().chain(layer.biases.iter()).copied().collect::<Vec<f64>>()).collect()
+    }
+
+    // The run loop before the parent's evaluation was carried forward: the current network is evaluated again every generation
+    fn legacyRun(mut nn: NANN, mut mutationAlgorithm: Box<dyn MutationAlgorithm>, context: &ExperimentContext, maxGenerations: i32) -> Vec<(i32, Vec<f64>)> {
+        let mut accepted = vec![];
+        for i in 1..=maxGenerations {
+            let evaluation = mutationAlgorithm.getProblem().evaluate(&nn, context);
+            if evaluation.success {
+                break;
+            }
+            let mutation = mutationAlgorithm.mutate(nn.clone(), evaluation.score, context);
+            if mutation.accepted {
+                accepted.push((i, parameters(&mutation.nn)));
+            }
+            nn = mutation.nn;
+        }
+        accepted
+    }
+
+    fn config(problem: &str, algorithm: AlgorithmEnum, inputDim: usize, outputDim: usize) -> ExperimentConfig {
+        let mut config = ExperimentConfig::default();
+        config.problem = ProblemEnum::fromName(problem).unwrap();
+        config.algorithm = algorithm;
+        config.inputDim = inputDim;
+        config.hiddenDims = vec![2];
+        config.outputDim = outputDim;
+        config.r = 30.0;
+        config.numberOfPoints = 200;
+        config.seed = 1234;
+        config.maxGenerations = Some(300);
+        config
+    }
+
+    fn acceptedSequences(config: &ExperimentConfig) -> (Vec<(i32, Vec<f64>)>, Vec<(i32, Vec<f64>)>) {
+        let context = config.context();
+        let maxGenerations = config.maxGenerations.unwrap() as i32;
+
+        let (nn, problem, algorithmSeed) = experiment::setupIteration(config, 1);
+        let mutationAlgorithm = experiment::buildAlgorithm(config, &nn, problem, algorithmSeed);
+        let legacy = legacyRun(nn, mutationAlgorithm, &context, maxGenerations);
+
+        let (nn, problem, algorithmSeed) = experiment::setupIteration(config, 1);
+        let (problem, evaluations) = CountingProblem::wrap(problem);
+        let mutationAlgorithm = experiment::buildAlgorithm(config, &nn, problem, algorithmSeed);
+        let mut recorder = AcceptedRecorder::default();
+        run(nn, mutationAlgorithm, &context, &evaluations, &mut recorder);
+        (legacy, recorder.accepted)
+    }
+
+    #[test]
+    fn acceptedSequenceIsUnchangedForEveryAlgorithm() {
+        let problems = [("Sphere Continuous Quarter", 2, 2), ("Sphere Discrete 2D Two Quarters", 4, 2), ("Cartpole N Steps", 5, 1)];
+        for (problem, inputDim, outputDim) in problems {
+            for (_, algorithm) in AlgorithmEnum::all() {
+                let mut config = config(problem, algorithm, inputDim, outputDim);
+                // Every generation of these evaluates a whole population
+                if algorithm.hasOwnPopulation() {
+                    config.maxGenerations = Some(30);
+                }
+                let (legacy, cached) = acceptedSequences(&config);
+                assert!(!legacy.is_empty(), "{:?} on {} accepted no mutation", algorithm, problem);
+                assert_eq!(legacy, cached, "{:?} on {} accepted a different sequence of networks", algorithm, problem);
+            }
+        }
+    }
+
+    #[test]
+    fn parentIsEvaluatedOnlyOnce() {
+        let mut config = config("Sphere Continuous Quarter", AlgorithmEnum::OnePlusOneNA, 2, 2);
+        // Unreachable optimum, so every generation is ran
+        config.optimum = 10.0;
+        let (nn, problem, algorithmSeed) = experiment::setupIteration(&config, 1);
+        let (problem, evaluations) = CountingProblem::wrap(problem);
+        let mutationAlgorithm = experiment::buildAlgorithm(&config, &nn, problem, algorithmSeed);
+        run(nn, mutationAlgorithm, &config.context(), &evaluations, &mut AcceptedRecorder::default());
+        // One evaluation of the initial network and one per mutated candidate
+        assert_eq!(evaluations.get(), 301);
+    }
+}
@@ -0,0 +1,377 @@
+use std::fs;
+
+use ndarray::{Array1, Array2};
+use serde::{Deserialize, Serialize};
+
+use super::activation::Activation;
+use super::ann::{Layer, NANN};
+
+/*
+    Saving and loading networks. Two formats hold the same information (layer weights, biases, connection masks, activations, the bias flag,
+    and the resolution and optimum the network was evolved with):
+      - JSON, for files ending in .json, readable by other tools. The mask is only written when a connection is disabled
+      - A compact little-endian binary format for everything else:
+            "NANN" magic, u16 version, u8 flags (bit 0: using bias), since version 2 f64 resolution and f64 optimum,
+            u32 number of layers, then for each layer
+            u8 activation, u32 inputs, u32 outputs, inputs * outputs f64 weights (row-major), outputs f64 biases
+            and, since version 3, the mask as inputs * outputs bits (row-major, least significant bit first, padded to a byte)
+    Both carry a version number, and files from newer versions are rejected. Version 1 files have no resolution or optimum,
+    and files before version 3 have every connection enabled.
+ */
+pub const FORMAT_VERSION: u16 = 3;
+const MAGIC: &[u8; 4] = b"NANN";
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetworkJson {
+    format: String,
+    version: u16,
+    usingBias: bool,
+    // Missing before version 2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resolution: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optimum: Option<f64>,
+    layers: Vec<LayerJson>
+}
+
+// Experiment parameters a network was evolved with, which its score depends on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkMetadata {
+    pub resolution: f64,
+    pub optimum: f64
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayerJson {
+    activation: String,
+    // One row per input, one column per output
+    weights: Vec<Vec<f64>>,
+    biases: Vec<f64>,
+    // Same shape as the weights, false for disabled connections. Every connection is enabled when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mask: Option<Vec<Vec<bool>>>
+}
+
+// Stable identifiers of the activations in the binary format
+fn activationCode(activation: Activation) -> u8 {
+    match activation {
+        Activation::Identity => 0,
+        Activation::Sign => 1,
+        Activation::ReLU => 2,
+        Activation::Tanh => 3,
+        Activation::Sigmoid => 4,
+        Activation::BinaryThreshold => 5,
+    }
+}
+
+fn activationFromCode(code: u8) -> Result<Activation, String> {
+    match code {
+        0 => Ok(Activation::Identity),
+        1 => Ok(Activation::Sign),
+        2 => Ok(Activation::ReLU),
+        3 => Ok(Activation::Tanh),
+        4 => Ok(Activation::Sigmoid),
+        5 => Ok(Activation::BinaryThreshold),
+        _ => Err(format!("Unknown activation code {}", code))
+    }
+}
+
+fn checkVersion(version: u16) -> Result<(), String> {
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(format!("Unsupported network file version {} (supported up to {})", version, FORMAT_VERSION));
+    }
+    Ok(())
+}
+
+fn isJson(path: &str) -> bool {
+    path.to_lowercase().ends_with(".json")
+}
+
+// Reads sequentially from the bytes of a binary network file
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl ByteReader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], String> {
+        if self.bytes.len() - self.position < count {
+            return Err("Unexpected end of network file".to_string());
+        }
+        self.position += count;
+        Ok(&self.bytes[self.position - count..self.position])
+    }
+
+    fn readU8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn readU16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn readU32(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn readF64s(&mut self, count: usize) -> Result<Vec<f64>, String> {
+        let bytes = self.take(count.checked_mul(8).ok_or("Layer too large".to_string())?)?;
+        Ok(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect())
+    }
+}
+
+impl NANN {
+    pub fn toJson(&self, metadata: &NetworkMetadata) -> String {
+        let network = NetworkJson {
+            format: "nann".to_string(),
+            version: FORMAT_VERSION,
+            usingBias: self.isUsingBias(),
+            resolution: Some(metadata.resolution),
+            optimum: Some(metadata.optimum),
+            layers: self.layers.iter().map(|layer| LayerJson {
+                activation: format!("{:?}", layer.activation),
+                weights: layer.weights.rows().into_iter().map(|row| row.to_vec()).collect(),
+                biases: layer.biases.to_vec(),
+                mask: if layer.mask.iter().all(|enabled| *enabled) { None } else { Some(layer.mask.rows().into_iter().map(|row| row.to_vec()).collect()) }
+            }).collect()
+        };
+        serde_json::to_string_pretty(&network).expect("Networks are always serializable")
+    }
+
+    // The network, and the parameters it was evolved with if the file records them
+    pub fn fromJson(json: &str) -> Result<(NANN, Option<NetworkMetadata>), String> {
+        let network: NetworkJson = serde_json::from_str(json).map_err(|e| format!("Invalid network file: {}", e))?;
+        if network.format != "nann" {
+            return Err(format!("Not a network file: format {}", network.format));
+        }
+        checkVersion(network.version)?;
+        let metadata = match (network.resolution, network.optimum) {
+            (Some(resolution), Some(optimum)) => Some(NetworkMetadata { resolution, optimum }),
+            _ => None
+        };
+        let layers = network.layers.into_iter().map(|layer| {
+            let activation = Activation::fromName(&layer.activation).ok_or(format!("Unknown activation: {}", layer.activation))?;
+            let inputs = layer.weights.len();
+            let outputs = layer.weights.first().map_or(0, |row| row.len());
+            if layer.weights.iter().any(|row| row.len() != outputs) {
+                return Err("Every row of a layer's weights must have the same length".to_string());
+            }
+            let mask = match layer.mask {
+                Some(mask) => Array2::from_shape_vec((mask.len(), mask.first().map_or(0, |row| row.len())), mask.concat()).map_err(|e| e.to_string())?,
+                None => Array2::from_elem((inputs, outputs), true)
+            };
+            Ok(Layer {
+                weights: Array2::from_shape_vec((inputs, outputs), layer.weights.concat()).map_err(|e| e.to_string())?,
+                biases: Array1::from_vec(layer.biases),
+                mask,
+                activation
+            })
+        }).collect::<Result<Vec<Layer>, String>>()?;
+        Ok((NANN::fromLayers(layers, network.usingBias)?, metadata))
+    }
+
+    pub fn toBytes(&self, metadata: &NetworkMetadata) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        bytes.push(self.isUsingBias() as u8);
+        bytes.extend(metadata.resolution.to_le_bytes());
+        bytes.extend(metadata.optimum.to_le_bytes());
+        bytes.extend((self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            let (inputs, outputs) = layer.weights.dim();
+            bytes.push(activationCode(layer.activation));
+            bytes.extend((inputs as u32).to_le_bytes());
+            bytes.extend((outputs as u32).to_le_bytes());
+            layer.weights.iter().chain(layer.biases.iter()).for_each(|x| bytes.extend(x.to_le_bytes()));
+            let mask: Vec<bool> = layer.mask.iter().copied().collect();
+            bytes.extend(mask.chunks(8).map(|bits| bits.iter().enumerate().fold(0u8, |byte, (i, enabled)| byte | ((*enabled as u8) << i))));
+        }
+        bytes
+    }
+
+    // The network, and the parameters it was evolved with if the file records them
+    pub fn fromBytes(bytes: &[u8]) -> Result<(NANN, Option<NetworkMetadata>), String> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(4).ok() != Some(MAGIC.as_slice()) {
+            return Err("Not a network file".to_string());
+        }
+        let version = reader.readU16()?;
+        checkVersion(version)?;
+        let usingBias = reader.readU8()? & 1 == 1;
+        let metadata = if version >= 2 {
+            let values = reader.readF64s(2)?;
+            Some(NetworkMetadata { resolution: values[0], optimum: values[1] })
+        } else {
+            None
+        };
+        let numberOfLayers = reader.readU32()?;
+        let mut layers = vec![];
+        for _ in 0..numberOfLayers {
+            let activation = activationFromCode(reader.readU8()?)?;
+            let inputs = reader.readU32()?;
+            let outputs = reader.readU32()?;
+            let connections = inputs.checked_mul(outputs).ok_or("Layer too large".to_string())?;
+            let weights = reader.readF64s(connections)?;
+            let biases = reader.readF64s(outputs)?;
+            let mask: Vec<bool> = if version >= 3 {
+                let bytes = reader.take(connections.div_ceil(8))?;
+                (0..connections).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect()
+            } else {
+                vec![true; connections]
+            };
+            layers.push(Layer {
+                weights: Array2::from_shape_vec((inputs, outputs), weights).map_err(|e| e.to_string())?,
+                biases: Array1::from_vec(biases),
+                mask: Array2::from_shape_vec((inputs, outputs), mask).map_err(|e| e.to_string())?,
+                activation
+            });
+        }
+        if reader.position != bytes.len() {
+            return Err("Unexpected data at the end of network file".to_string());
+        }
+        Ok((NANN::fromLayers(layers, usingBias)?, metadata))
+    }
+
+    // Writes the network and the parameters it was evolved with to `path`, as JSON if it ends in .json and in the binary format otherwise
+    pub fn save(&self, path: &str, metadata: &NetworkMetadata) -> Result<(), String> {
+        let result = if isJson(path) { fs::write(path, self.toJson(metadata)) } else { fs::write(path, self.toBytes(metadata)) };
+        result.map_err(|e| format!("Unable to write network file {}: {}", path, e))
+    }
+
+    // Reads a network saved by `save`, choosing the format by the file's extension
+    pub fn load(path: &str) -> Result<(NANN, Option<NetworkMetadata>), String> {
+        if isJson(path) {
+            NANN::fromJson(&fs::read_to_string(path).map_err(|e| format!("Unable to read network file {}: {}", path, e))?)
+        } else {
+            NANN::fromBytes(&fs::read(path).map_err(|e| format!("Unable to read network file {}: {}", path, e))?)
+        }
+    }
+}
+
+// Format networks are saved in when the runner exports them
+#[derive(Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkFormat {
+    Json,
+    Binary
+}
+
+impl NetworkFormat {
+    pub fn fromName(name: &str) -> Option<NetworkFormat> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(NetworkFormat::Json),
+            "binary" | "nann" => Some(NetworkFormat::Binary),
+            _ => None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            NetworkFormat::Json => "json",
+            NetworkFormat::Binary => "nann"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const METADATA: NetworkMetadata = NetworkMetadata { resolution: 30.0, optimum: 0.9 };
+
+    // Two layers, the first with more connections than fit in a byte of the mask
+    fn network() -> NANN {
+        NANN::new(vec![(3, 4), (4, 2)], &[Activation::ReLU, Activation::Tanh], 30.0, true, &mut StdRng::seed_from_u64(7))
+    }
+
+    fn sparseNetwork() -> NANN {
+        let mut nn = network();
+        nn.layers[0].toggleConnection(0, 1);
+        nn.layers[0].toggleConnection(2, 3);
+        nn.layers[1].toggleConnection(3, 0);
+        nn
+    }
+
+    fn assertSameNetwork(expected: &NANN, actual: &NANN) {
+        assert_eq!(expected.isUsingBias(), actual.isUsingBias());
+        assert_eq!(expected.layers.len(), actual.layers.len());
+        for (a, b) in expected.layers.iter().zip(&actual.layers) {
+            assert_eq!(a.weights, b.weights);
+            assert_eq!(a.biases, b.biases);
+            assert_eq!(a.mask, b.mask);
+            assert_eq!(a.activation, b.activation);
+        }
+    }
+
+    // A binary file as written by an older version, which had no mask and, in version 1, no metadata
+    fn legacyBytes(nn: &NANN, version: u16) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(version.to_le_bytes());
+        bytes.push(nn.isUsingBias() as u8);
+        if version >= 2 {
+            bytes.extend(METADATA.resolution.to_le_bytes());
+            bytes.extend(METADATA.optimum.to_le_bytes());
+        }
+        bytes.extend((nn.layers.len() as u32).to_le_bytes());
+        for layer in &nn.layers {
+            let (inputs, outputs) = layer.weights.dim();
+            bytes.push(activationCode(layer.activation));
+            bytes.extend((inputs as u32).to_le_bytes());
+            bytes.extend((outputs as u32).to_le_bytes());
+            layer.weights.iter().chain(layer.biases.iter()).for_each(|x| bytes.extend(x.to_le_bytes()));
+        }
+        bytes
+    }
+
+    #[test]
+    fn binaryRoundTripKeepsMasksAndMetadata() {
+        let nn = sparseNetwork();
+        let (loaded, metadata) = NANN::fromBytes(&nn.toBytes(&METADATA)).unwrap();
+        assertSameNetwork(&nn, &loaded);
+        assert_eq!(metadata, Some(METADATA));
+    }
+
+    #[test]
+    fn jsonRoundTripKeepsMasksAndMetadata() {
+        let nn = sparseNetwork();
+        let (loaded, metadata) = NANN::fromJson(&nn.toJson(&METADATA)).unwrap();
+        assertSameNetwork(&nn, &loaded);
+        assert_eq!(metadata, Some(METADATA));
+    }
+
+    #[test]
+    fn olderBinaryVersionsAreRead() {
+        // Neither version had a mask, so every connection is enabled
+        let nn = network();
+        let (loaded, metadata) = NANN::fromBytes(&legacyBytes(&nn, 1)).unwrap();
+        assertSameNetwork(&nn, &loaded);
+        assert!(loaded.layers.iter().all(|layer| layer.mask.iter().all(|enabled| *enabled)));
+        assert_eq!(metadata, None);
+
+        let (loaded, metadata) = NANN::fromBytes(&legacyBytes(&nn, 2)).unwrap();
+        assertSameNetwork(&nn, &loaded);
+        assert_eq!(metadata, Some(METADATA));
+    }
+
+    #[test]
+    fn olderJsonVersionsAreRead() {
+        let nn = network();
+        let mut json: serde_json::Value = serde_json::from_str(&nn.toJson(&METADATA)).unwrap();
+        json["version"] = 1.into();
+        json.as_object_mut().unwrap().remove("resolution");
+        json.as_object_mut().unwrap().remove("optimum");
+        let (loaded, metadata) = NANN::fromJson(&json.to_string()).unwrap();
+        assertSameNetwork(&nn, &loaded);
+        assert_eq!(metadata, None);
+    }
+
+    #[test]
+    fn newerVersionsAreRejected() {
+        let mut bytes = network().toBytes(&METADATA);
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(NANN::fromBytes(&bytes).is_err());
+    }
+}
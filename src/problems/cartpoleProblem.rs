@@ -1,19 +1,20 @@
 
 use crate::nn::ann::NANN;
+use crate::utils::experimentContext::ExperimentContext;
 use std::f64::consts;
 use std::fmt;
 use ndarray::*;
 use rand::*;
 use rand::SeedableRng;
 
-use super::naProblem::NAProblem;
+use super::naProblem::{Evaluation, EvaluationPayload, NAProblem};
 
 const GRAVITY_PER_TYPESTEP: f64 = 0.327;
 const POLE_LENGTH: f64 = 10.0;
 const CART_STEP_SIZE: f64 = 0.6;
 
 pub struct CartpoleProblem {
-    directionFunction: fn(f64) -> f64,
+    directionFunction: fn(f64, &ExperimentContext) -> f64,
     problemName: String,
     seed: u64
 }
@@ -22,33 +23,36 @@ impl CartpoleProblem {
 
     /**
      * Creates a new CartpoleProblem instance where the neural network's output will be ran through the provided `directionFunction`.
+     * The `seed` determines the initial position of the cart and angle of the pole.
      * See `CartpoleProblem::Default` and `CartpoleProblem::new*` for examples
      */
-    pub fn new(directionFunction: fn(f64) -> f64, problemName: String) -> Box<dyn NAProblem> {
+    pub fn new(directionFunction: fn(f64, &ExperimentContext) -> f64, problemName: String, seed: u64) -> Box<dyn NAProblem> {
         Box::new(CartpoleProblem {
             directionFunction,
             problemName,
-            seed: rand::thread_rng().gen_range(0..10000)
+            seed
         })
     }
 
     /**
      * The neural network's output will be directly used as the distance to move in a given direction.
      */
-    pub fn default() -> Box<dyn NAProblem> {
+    pub fn default(seed: u64) -> Box<dyn NAProblem> {
         CartpoleProblem::new(
-            |x| x,
-            String::from("Cartpole continuous")
+            |x, _| x,
+            String::from("Cartpole continuous"),
+            seed
         )
     }
 
     /**
      * The neural network's output will be used as the distance to move the cart in a given direction, but rounded to the closest 1/r, discritizing the domain.
      */
-    pub fn newDiscretizedContinuous() -> Box<dyn NAProblem> {
+    pub fn newDiscretizedContinuous(seed: u64) -> Box<dyn NAProblem> {
         CartpoleProblem::new(
-            |x|  x * CART_STEP_SIZE.round() / CART_STEP_SIZE ,
+            |x, _|  x * CART_STEP_SIZE.round() / CART_STEP_SIZE ,
             String::from("Cartpole discrete"),
+            seed
         )
     }
 
@@ -56,12 +60,13 @@ impl CartpoleProblem {
      * The neural network's output will be treated as a binary result, determining if the cart moves left or right a single step of size CART_STEP_SIZE
      * With certain step sizes, will get stuck in a local optimum
      */
-    pub fn newSingleStep() -> Box<dyn NAProblem> {
+    pub fn newSingleStep(seed: u64) -> Box<dyn NAProblem> {
         CartpoleProblem::new(
-            |x: f64| {
+            |x: f64, _| {
                 x.signum() * CART_STEP_SIZE
             },
             String::from("Cartpole single step"),
+            seed
         )
     }
 
@@ -69,17 +74,18 @@ impl CartpoleProblem {
      * The neural network's output will be translated into an integer, corresponding to how many steps of a fixed size the cart will move.
      * At least one step will be taken, no matter what.
      */
-    pub fn newNSteps() -> Box<dyn NAProblem> {
+    pub fn newNSteps(seed: u64) -> Box<dyn NAProblem> {
         CartpoleProblem::new(
-            |x| {
+            |x, context| {
                 let result = match x {
                     y if -1.0 < y && y < 0.0 => -1.0,
                     y if 0.0 <= y && y < 1.0 => 1.0,
                     _ => x.round()
                 };
-                result * 10.0 / unsafe { crate::R }
+                result * 10.0 / context.resolution
             },
             String::from("Cartpole N Steps"),
+            seed
         )
     }
 
@@ -91,8 +97,17 @@ impl fmt::Display for CartpoleProblem {
     }
 }
 
-impl NAProblem for CartpoleProblem {
-    fn evaluate(&self, nn: &NANN) -> (bool, f64, Array2<f64>) {
+// State of the simulation after one timestep, and the acceleration the network chose for it
+pub struct CartpoleStep {
+    pub cartX: f64,
+    pub cartVelocity: f64,
+    pub poleAngle: f64,
+    pub acceleration: f64
+}
+
+impl CartpoleProblem {
+    // Runs the simulation controlled by `nn` until the pole falls, the cart leaves the track or the time runs out. Returns whether the pole was balanced and every step
+    fn simulate(&self, nn: &NANN, context: &ExperimentContext) -> (bool, Vec<CartpoleStep>) {
         let mut randGen = rngs::StdRng::seed_from_u64(self.seed);
 
         let mut cartX: f64 = randGen.gen_range(-5.0..=5.0);
@@ -101,16 +116,15 @@ impl NAProblem for CartpoleProblem {
         let mut poleAngularVelocity: f64 = 0.0;
         let mut poleAngularAcceleration: f64 = 0.0;
         let mut timestep: f64 = 0.0;
-        let mut directionHistory: Vec<f64> = vec!();
+        let mut steps: Vec<CartpoleStep> = vec!();
 
-        let result: (bool, f64) = loop  {
+        let success = loop  {
             // Requests an output from the neural network with the current state of the problem.
             // Casts the prediction into a float and runs it through the direction function provided on initialization
             let acceleration: f64 = (self.directionFunction)(*nn.clone().forward(
                 Array2::<f64>::from_shape_vec(Ix2(1usize, 5usize),
                 vec![poleAngle, poleAngularVelocity, poleAngularAcceleration, cartX, cartVelocity]).unwrap()
-            ).get((0, 0)).unwrap() as f64);
-            directionHistory.push(acceleration);
+            ).get((0, 0)).unwrap() as f64, context);
             cartVelocity += acceleration;
             cartX += cartVelocity;
 
@@ -119,16 +133,55 @@ impl NAProblem for CartpoleProblem {
             poleAngularVelocity += poleAngularAcceleration;
             poleAngle += poleAngularVelocity;
             timestep += 1.0;
+            steps.push(CartpoleStep { cartX, cartVelocity, poleAngle, acceleration });
 
             if poleAngle.abs() >= consts::PI as f64 / 16.0 || cartX.abs() >= 50.0 {
-                break (false, timestep);
+                break false;
             }
             if timestep >= 900.0 {
-                break (true, timestep);
+                break true;
             }
         };
+        (success, steps)
+    }
+}
+
+impl NAProblem for CartpoleProblem {
+    fn evaluate(&self, nn: &NANN, context: &ExperimentContext) -> Evaluation {
+        let (success, steps) = self.simulate(nn, context);
+        // Final cart position on the track and pole angle, scaled from the limits that end the simulation
+        let last = steps.last().expect("The simulation runs at least one step");
+        let behaviour = vec![
+            ((last.cartX + 50.0) / 100.0).clamp(0.0, 1.0),
+            ((last.poleAngle + consts::PI / 16.0) / (consts::PI / 8.0)).clamp(0.0, 1.0)
+        ];
         // Unlike the other problems, doesn't just return the last prediction. This problem has a temporal aspect, and returns all predictions for the current evaluation.
-        return (result.0, result.1, Array2::from_shape_vec((1, directionHistory.len()), directionHistory).unwrap());
+        Evaluation {
+            success,
+            score: steps.len() as f64,
+            objectives: vec![],
+            steps: steps.len(),
+            behaviour,
+            payload: EvaluationPayload::Actions(steps.iter().map(|step| step.acceleration).collect())
+        }
     }
-    
-}
\ No newline at end of file
+
+    // Prints the trajectory one step per line, with the cart drawn on the track ('|' marks its ends) and the pole's angle
+    fn render(&self, nn: &NANN, context: &ExperimentContext) -> String {
+        let (success, steps) = self.simulate(nn, context);
+        let mut report = format!("Problem: {}\nScore: {}\nSuccess: {}\n{}\n", self, steps.len(), success,
+            if success { "The pole was balanced until the end" } else { "The pole fell or the cart left the track" });
+        report.push_str("Step   Acceleration   Cart Position   Cart Velocity   Pole Angle\n");
+        let trackWidth = 51;
+        for (index, step) in steps.iter().enumerate() {
+            let mut track: Vec<char> = vec!['-'; trackWidth];
+            track[0] = '|';
+            track[trackWidth - 1] = '|';
+            let position = ((step.cartX + 50.0) / 100.0 * (trackWidth - 1) as f64).round().clamp(0.0, (trackWidth - 1) as f64) as usize;
+            track[position] = 'o';
+            report.push_str(&format!("{:>4}   {:>12.4}   {:>13.4}   {:>13.4}   {:>9.3}°   {}\n",
+                index + 1, step.acceleration, step.cartX, step.cartVelocity, step.poleAngle.to_degrees(), track.into_iter().collect::<String>()));
+        }
+        report
+    }
+}
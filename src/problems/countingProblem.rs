@@ -0,0 +1,51 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::nn::ann::NANN;
+use crate::utils::experimentContext::ExperimentContext;
+
+use super::naProblem::{Evaluation, NAProblem};
+
+// Shared count of the evaluations made through a CountingProblem
+#[derive(Clone, Default)]
+pub struct EvaluationCounter(Arc<AtomicU64>);
+
+impl EvaluationCounter {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/*
+    Wraps a problem and counts every call to `evaluate`, including the ones mutation algorithms make internally.
+    The count is read through the EvaluationCounter returned by `wrap`, since the problem itself is owned by the mutation algorithm.
+ */
+pub struct CountingProblem {
+    problem: Box<dyn NAProblem>,
+    counter: EvaluationCounter
+}
+
+impl CountingProblem {
+    pub fn wrap(problem: Box<dyn NAProblem>) -> (Box<dyn NAProblem>, EvaluationCounter) {
+        let counter = EvaluationCounter::default();
+        (Box::new(CountingProblem { problem, counter: counter.clone() }), counter)
+    }
+}
+
+impl fmt::Display for CountingProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.problem)
+    }
+}
+
+impl NAProblem for CountingProblem {
+    fn evaluate(&self, nn: &NANN, context: &ExperimentContext) -> Evaluation {
+        self.counter.0.fetch_add(1, Ordering::Relaxed);
+        self.problem.evaluate(nn, context)
+    }
+
+    fn render(&self, nn: &NANN, context: &ExperimentContext) -> String {
+        self.problem.render(nn, context)
+    }
+}
@@ -1,11 +1,57 @@
 use std::fmt::Display;
 
 use crate::nn::ann::NANN;
+use crate::utils::experimentContext::ExperimentContext;
 use ndarray::*;
 
+/*
+    Result of evaluating a network on a problem.
+ */
+#[derive(Clone)]
+pub struct Evaluation {
+    pub success: bool,
+    pub score: f64,
+    // Named components of the score, e.g. the share of true positives and true negatives. Empty if the score has a single component
+    pub objectives: Vec<(&'static str, f64)>,
+    // Number of times the network was queried during the evaluation
+    pub steps: usize,
+    // Two values in [0, 1] describing how the network behaves rather than how well, for MAP-Elites and novelty search
+    pub behaviour: Vec<f64>,
+    pub payload: EvaluationPayload
+}
+
+#[derive(Clone)]
+pub enum EvaluationPayload {
+    // Output of the network for the problem's input, for the sphere problems
+    Prediction(Array2<f64>),
+    // Acceleration chosen at every timestep, for the cartpole problems
+    Actions(Vec<f64>)
+}
+
+impl Evaluation {
+    // Payload as a matrix, as exported in the Solution column of the results
+    pub fn solution(&self) -> Array2<f64> {
+        match &self.payload {
+            EvaluationPayload::Prediction(prediction) => prediction.clone(),
+            EvaluationPayload::Actions(actions) => Array2::from_shape_vec((1, actions.len()), actions.clone()).unwrap()
+        }
+    }
+
+    // Objectives in the form "name=value", separated by ';'
+    pub fn objectivesString(&self) -> String {
+        self.objectives.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<String>>().join(";")
+    }
+}
+
 /*
     Base trait for all problem types.
  */
-pub trait NAProblem: Display {
-    fn evaluate(&self, nn: &NANN) -> (bool, f64, Array2<f64>);
-}
\ No newline at end of file
+pub trait NAProblem: Display + Send {
+    fn evaluate(&self, nn: &NANN, context: &ExperimentContext) -> Evaluation;
+
+    // Human readable report of how `nn` performs on the problem, used to inspect saved networks
+    fn render(&self, nn: &NANN, context: &ExperimentContext) -> String {
+        let evaluation = self.evaluate(nn, context);
+        format!("Problem: {}\nScore: {}\nSuccess: {}\n", self, evaluation.score, evaluation.success)
+    }
+}
@@ -1,6 +1,6 @@
-use crate::problems::naProblem::NAProblem;
+use crate::problems::naProblem::{Evaluation, EvaluationPayload, NAProblem};
 use crate::nn::ann::NANN;
-use crate::utils::{interval::Interval, mathUtils};
+use crate::utils::{interval::Interval, mathUtils, experimentContext::ExperimentContext};
 use std::fmt;
 use std::f64::consts;
 use ndarray::*;
@@ -41,6 +41,31 @@ impl SphereContinuousNAProblem {
         )
     }
 
+    // Arcs of the unit circle covered by the network's output lines
+    fn predictedArcs(&self, prediction: &Array2<f64>) -> Interval {
+        Interval::fromVec(prediction.rows().into_iter().fold(vec![], |mut acc, row| {
+            acc.extend::<Vec<(f64, f64)>>(row.axis_chunks_iter(Axis(0), 2).map(|pair| {
+                SphereContinuousNAProblem::arc(*pair.index(0), *pair.index(1))
+            }).collect());
+            acc
+        }))
+    }
+
+    // Arc of the unit circle covered by the output line with the given angle and bias, as (start angle, end angle)
+    fn arc(angle: f64, bias: f64) -> (f64, f64) {
+        let bias: f64 = mathUtils::ring(bias, 1.0, true);
+
+        //Transforming the parameters of the output line into the covered arc in the unit circle
+        let start_angle = mathUtils::ring(angle - bias.acos(), 2.0*consts::PI, false);
+        let end_angle = mathUtils::ring(bias.acos() + angle, 2.0*consts::PI, false);
+        (start_angle, end_angle)
+    }
+
+    fn predict(&self, nn: &NANN) -> Array2<f64> {
+        let inputs: Vec<f64> = self.ranges.iter().fold(vec![], |mut acc, (x_1, x_2)| {acc.append(vec![*x_1, *x_2].as_mut()); acc});
+        (*nn).clone().forward(Array2::<f64>::from_shape_vec(Ix2(1usize, inputs.len()), inputs).unwrap())
+    }
+
     pub fn newLocalOpt() -> Box<dyn NAProblem> {
         SphereContinuousNAProblem::new( 
             vec![
@@ -61,36 +86,64 @@ impl fmt::Display for SphereContinuousNAProblem {
 }
 
 impl NAProblem for SphereContinuousNAProblem {
-    fn evaluate(&self, nn: &NANN) -> (bool, f64, Array2<f64>) {
-    
-        let inputs: Vec<f64> = self.ranges.iter().fold(vec![], |mut acc, (x_1, x_2)| {acc.append(vec![*x_1, *x_2].as_mut()); acc});
-        let prediction: Array2<f64> = (*nn).clone().forward(Array2::<f64>::from_shape_vec(Ix2(1usize, inputs.len()), inputs).unwrap());
+    fn evaluate(&self, nn: &NANN, context: &ExperimentContext) -> Evaluation {
+        let prediction = self.predict(nn);
         let givenRanges = Interval::fromVec(self.ranges.clone());
-        let predictionRanges = Interval::fromVec(prediction.rows().into_iter().fold(vec![], |mut acc, row| {
-            acc.extend::<Vec<(f64, f64)>>(row.axis_chunks_iter(Axis(0), 2).map(|pair| {
-                let angle: f64 = *pair.index(0);
-                let bias: f64 = mathUtils::ring(*pair.index(1), 1.0, true);
-                
-                //Transforming the parameters of the output line into the covered arc in the unit circle
-                let start_angle = mathUtils::ring(angle - bias.acos(), 2.0*consts::PI, false);
-                let end_angle = mathUtils::ring(bias.acos() + angle, 2.0*consts::PI, false);
-                (start_angle, end_angle)
-            }).collect());
-            acc
-        }));
+        let predictionRanges = self.predictedArcs(&prediction);
+        let area = |interval: &Interval| interval.ranges.iter().fold(0.0, |acc, (x, y)| acc + (y - x));
         
         // Positives
         let mut correctPredictionRanges = predictionRanges.intersection(&givenRanges);
+        let truePositives = area(&correctPredictionRanges) / (2.0*consts::PI);
         
         // Negatives
         let givenComplement = &givenRanges.complement();
         let predictedComplement = &predictionRanges.complement();
         let complementIntersect = givenComplement.intersection(predictedComplement);
+        let trueNegatives = area(&complementIntersect) / (2.0*consts::PI);
         correctPredictionRanges = correctPredictionRanges.union(&complementIntersect);
         
-        let correctPredictionArea: f64 = correctPredictionRanges.ranges.iter().fold(0.0, |acc, (x, y)| acc + (y - x));
-        let success = unsafe { correctPredictionArea / (2.0*consts::PI) >= crate::OPTIMUM - (1.0 / crate::R) };
+        let correctPredictionArea: f64 = area(&correctPredictionRanges);
         let score = correctPredictionArea / (2.0*consts::PI);
-        return (success, score, prediction);
+        // Start and end angles of the first output line's arc
+        let (startAngle, endAngle) = SphereContinuousNAProblem::arc(prediction[(0, 0)], prediction[(0, 1)]);
+        Evaluation {
+            success: context.isSuccess(score),
+            score,
+            objectives: vec![("True Positives", truePositives), ("True Negatives", trueNegatives)],
+            steps: 1,
+            behaviour: vec![startAngle / (2.0*consts::PI), endAngle / (2.0*consts::PI)],
+            payload: EvaluationPayload::Prediction(prediction)
+        }
+    }
+
+    /*
+        Lists the target ranges and the predicted arcs in degrees, and draws both around the unit circle
+        in 5 degree cells, marking the cells whose centre is misclassified.
+     */
+    fn render(&self, nn: &NANN, context: &ExperimentContext) -> String {
+        let evaluation = self.evaluate(nn, context);
+        let givenRanges = Interval::fromVec(self.ranges.clone());
+        let predictionRanges = self.predictedArcs(&evaluation.solution());
+        let toDegrees = |ranges: &Interval| ranges.ranges.iter()
+            .map(|(x, y)| format!("[{:.1}°, {:.1}°]", x.to_degrees(), y.to_degrees()))
+            .collect::<Vec<String>>().join(" ");
+        let contains = |ranges: &Interval, angle: f64| ranges.ranges.iter().any(|(x, y)| *x <= angle && angle <= *y);
+
+        let cells = 72;
+        let mut targetLine = String::new();
+        let mut predictionLine = String::new();
+        let mut errorLine = String::new();
+        for cell in 0..cells {
+            let angle = (cell as f64 + 0.5) * 2.0 * consts::PI / cells as f64;
+            let (target, predicted) = (contains(&givenRanges, angle), contains(&predictionRanges, angle));
+            targetLine.push(if target { '#' } else { '.' });
+            predictionLine.push(if predicted { '#' } else { '.' });
+            errorLine.push(if target != predicted { '^' } else { ' ' });
+        }
+        format!("Problem: {}\nScore: {}\nSuccess: {}\nTarget ranges: {}\nPredicted arcs: {}\n\n           0°{}180°{}360°\nTarget     {}\nPredicted  {}\nErrors     {}\n",
+            self, evaluation.score, evaluation.success, toDegrees(&givenRanges), toDegrees(&predictionRanges),
+            " ".repeat(cells / 2 - 2), " ".repeat(cells / 2 - 8), targetLine, predictionLine, errorLine
+        )
     }
 }
\ No newline at end of file
@@ -1,9 +1,9 @@
-use crate::problems::naProblem::NAProblem;
+use crate::problems::naProblem::{Evaluation, EvaluationPayload, NAProblem};
 use crate::nn::ann::NANN;
-use crate::utils::mathUtils;
+use crate::utils::{mathUtils, experimentContext::ExperimentContext};
 use std::fmt;
 use std::f64::consts;
-use rand_distr::Distribution;
+use rand::{rngs, SeedableRng};
 use ndarray::*;
 
 pub struct SphereDiscreteNAProblem {
@@ -15,9 +15,9 @@ pub struct SphereDiscreteNAProblem {
 
 
 impl SphereDiscreteNAProblem {
-    pub fn new(ranges: Vec<Vec<(f64, f64)>>, numPoints: u32, problemName: String) -> Box<dyn NAProblem> {
-        // Creates the points to be used through out this execution, evaluates whether they are positive or negative, and converts them to a polar representation.
-        let points = mathUtils::nSpherePointGeneration(numPoints, ranges[0].len() + 1).iter().map(
+    pub fn new(ranges: Vec<Vec<(f64, f64)>>, numPoints: u32, problemName: String, seed: u64) -> Box<dyn NAProblem> {
+        // Creates the points to be used through out this execution from the given seed, evaluates whether they are positive or negative, and converts them to a polar representation.
+        let points = mathUtils::nSpherePointGeneration(numPoints, ranges[0].len() + 1, &mut rngs::StdRng::seed_from_u64(seed)).iter().map(
             |p| {
                 (mathUtils::cartesianToPolar(p), mathUtils::inRange(&mathUtils::cartesianToPolar(p), &ranges))
             }).collect();
@@ -29,35 +29,38 @@ impl SphereDiscreteNAProblem {
         })
     }
 
-    pub fn newQuarter(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newQuarter(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![vec![(0.0, consts::PI/2.0)]],
             numPoints,
-            String::from("Sphere Discrete Quarter")
+            String::from("Sphere Discrete Quarter"),
+            seed
         )
     }
 
 
-    pub fn newHalf(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newHalf(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
              vec![vec![(0.0, consts::PI)]],
             numPoints,
-            String::from("Sphere Discrete Half")
+            String::from("Sphere Discrete Half"),
+            seed
         )
     }
 
-    pub fn newTwoQuarters(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newTwoQuarters(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![
                 vec![(0.0, consts::PI/2.0)],
                 vec![(consts::PI, 3.0*consts::PI/2.0)]
             ],
             numPoints,
-            String::from("Sphere Discrete Two Quarters")
+            String::from("Sphere Discrete Two Quarters"),
+            seed
         )
     }
 
-    pub fn newLocalOpt(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newLocalOpt(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new( 
             vec![
                 vec![(0.0, consts::PI/3.0)],
@@ -65,74 +68,82 @@ impl SphereDiscreteNAProblem {
                 vec![(4.0*consts::PI/3.0, 11.0*consts::PI/6.0)]
             ],
             numPoints,
-            String::from("Sphere Discrete Local Optima")
+            String::from("Sphere Discrete Local Optima"),
+            seed
         )
     }
 
     
-    pub fn newCorner3D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newCorner3D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![vec![(0.0, consts::PI/2.0), (0.0, consts::PI/2.0)]],
             numPoints,
-            String::from("Sphere 3D Corner")
+            String::from("Sphere 3D Corner"),
+            seed
         )
     }
 
-    pub fn newHalf3D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newHalf3D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![vec![(0.0, consts::PI), (0.0, consts::PI)]],
             numPoints,
-            String::from("Sphere 3D Half")
+            String::from("Sphere 3D Half"),
+            seed
         )
     }
 
-    pub fn newSlice3D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newSlice3D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![vec![(0.0, consts::PI), (0.0, consts::PI/2.0)]],
             numPoints,
-            String::from("Sphere 3D Slice")
+            String::from("Sphere 3D Slice"),
+            seed
         )
     }
 
-    pub fn newTwoSlices3D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newTwoSlices3D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new( 
             vec![
                 vec![ (0.0, consts::PI/2.0), (0.0, consts::PI),],
                 vec![(consts::PI, 3.0*consts::PI/2.0), (consts::PI, consts::PI*2.0)]
             ],
             numPoints,
-            String::from("Sphere 3D Two Slices")
+            String::from("Sphere 3D Two Slices"),
+            seed
         )
     }
     
-    pub fn newQuarter4D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newQuarter4D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![
                 vec![(0.0, consts::PI), (0.0, consts::PI), (0.0, consts::PI)]
             ],
             numPoints,
-            String::from("Sphere 4D Quarter")
+            String::from("Sphere 4D Quarter"),
+            seed
         )
     }
         
-    pub fn newHalf4D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newHalf4D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![
                 vec![(0.0, 2.0*consts::PI), (0.0, consts::PI), (0.0, consts::PI)]
             ],
             numPoints,
-            String::from("Sphere 4D Half")
+            String::from("Sphere 4D Half"),
+            seed
         )
     }
         
-    pub fn newTwoQuarters4D(numPoints: u32) -> Box<dyn NAProblem> {
+    pub fn newTwoQuarters4D(numPoints: u32, seed: u64) -> Box<dyn NAProblem> {
         SphereDiscreteNAProblem::new(
             vec![
                 vec![(0.0, consts::PI), (0.0, consts::PI), (0.0, consts::PI)],
                 vec![(consts::PI, 2.0*consts::PI), (0.0, consts::PI), (0.0, consts::PI)]
             ],
             numPoints,
-            String::from("Sphere 4D Two Quarters")
+            String::from("Sphere 4D Two Quarters"),
+            seed
         )
     }
 }
@@ -144,10 +155,9 @@ impl fmt::Display for SphereDiscreteNAProblem {
 
 }
 
-impl NAProblem for SphereDiscreteNAProblem {
-    fn evaluate(&self, nn: &NANN) -> (bool, f64, Array2<f64>) {
-
-        // Shapes the ranges and requests a prediction from the network
+impl SphereDiscreteNAProblem {
+    // Shapes the ranges and requests a prediction from the network
+    fn predict(&self, nn: &NANN) -> Array2<f64> {
         let inputs: Vec<f64> = self.ranges.iter().fold(vec![], |mut acc, range| {
             acc.extend(range.iter().fold(vec![], |mut r: Vec<f64>, (x_1, x_2)| {
                 r.append(vec![*x_1, *x_2].as_mut()); r
@@ -155,10 +165,12 @@ impl NAProblem for SphereDiscreteNAProblem {
             acc
         });
 
-        let prediction: Array2<f64> = (*nn).clone().forward(Array2::<f64>::from_shape_vec(Ix2(1usize, inputs.len()), inputs).unwrap());
+        (*nn).clone().forward(Array2::<f64>::from_shape_vec(Ix2(1usize, inputs.len()), inputs).unwrap())
+    }
 
-        // Transforms the prediction into a vector with angles in [0, 2pi] and bias in [-1, 1]
-        let normalVectors = prediction.rows().into_iter().fold(vec![], |mut acc, row| {
+    // Transforms the prediction into a vector with angles in [0, 2pi] and bias in [-1, 1]
+    fn normalVectors(&self, prediction: &Array2<f64>) -> Vec<Vec<f64>> {
+        prediction.rows().into_iter().fold(vec![], |mut acc, row| {
             acc.extend::<Vec<Vec<f64>>>(row.axis_chunks_iter(Axis(0), self.ranges[0].len() + 1).map(|chunk| {
                 let mut vector = vec![];
                 for i in 0..chunk.len() {
@@ -171,23 +183,64 @@ impl NAProblem for SphereDiscreteNAProblem {
                 vector
             }).collect());
             acc
-        });
+        })
+    }
 
-        // Points in a positive range should be classified as positive by at least one of the output ranges.
-        // Points in a negative range should be classified as negative by all output ranges. This prevents a single positive point contributing to the score through multiple output ranges
-        let accurate_predictions: i32 = self.points.iter().fold(0, |acc, (point, correctClassification)| {
-            if *correctClassification {
-                if normalVectors.iter().any(|vector| mathUtils::abovePlane(point, vector)) {
-                    return acc + 1;
-                }
-            } else {
-                if normalVectors.iter().all(|vector| !mathUtils::abovePlane(point, vector)) {
-                    return acc + 1;
-                }
-            }
-            acc
-        });
-        return (unsafe { (accurate_predictions as f64 / self.numPoints as f64) >= crate::OPTIMUM - (1.0 / crate::R)}, accurate_predictions as f64 / self.numPoints as f64, prediction);
+    // Points in a positive range should be classified as positive by at least one of the output ranges.
+    // Points in a negative range should be classified as negative by all output ranges. This prevents a single positive point contributing to the score through multiple output ranges
+    fn isClassifiedCorrectly(point: &Vec<f64>, correctClassification: bool, normalVectors: &Vec<Vec<f64>>) -> bool {
+        if correctClassification {
+            normalVectors.iter().any(|vector| mathUtils::abovePlane(point, vector))
+        } else {
+            normalVectors.iter().all(|vector| !mathUtils::abovePlane(point, vector))
+        }
+    }
+}
 
+impl NAProblem for SphereDiscreteNAProblem {
+    fn evaluate(&self, nn: &NANN, context: &ExperimentContext) -> Evaluation {
+        let prediction = self.predict(nn);
+        let normalVectors = self.normalVectors(&prediction);
+        let (truePositives, trueNegatives) = self.points.iter()
+            .filter(|(point, correctClassification)| SphereDiscreteNAProblem::isClassifiedCorrectly(point, *correctClassification, &normalVectors))
+            .fold((0, 0), |(positives, negatives), (_, correctClassification)| {
+                if *correctClassification { (positives + 1, negatives) } else { (positives, negatives + 1) }
+            });
+        let score = (truePositives + trueNegatives) as f64 / self.numPoints as f64;
+        // First normal angle and bias of the first predicted hyperplane
+        let firstPlane = &normalVectors[0];
+        let behaviour = vec![firstPlane[0] / (2.0*consts::PI), (firstPlane[firstPlane.len() - 1] + 1.0) / 2.0];
+        Evaluation {
+            success: context.isSuccess(score),
+            score,
+            objectives: vec![
+                ("True Positives", truePositives as f64 / self.numPoints as f64),
+                ("True Negatives", trueNegatives as f64 / self.numPoints as f64)
+            ],
+            steps: 1,
+            behaviour,
+            payload: EvaluationPayload::Prediction(prediction)
+        }
+    }
+
+    // Lists the predicted hyperplanes and every misclassified point, in polar coordinates (degrees)
+    fn render(&self, nn: &NANN, context: &ExperimentContext) -> String {
+        let evaluation = self.evaluate(nn, context);
+        let normalVectors = self.normalVectors(&evaluation.solution());
+        let toDegrees = |angles: &[f64]| angles.iter().map(|a| format!("{:.1}°", a.to_degrees())).collect::<Vec<String>>().join(", ");
+        let mut report = format!("Problem: {}\nScore: {}\nSuccess: {}\nPredicted hyperplanes (normal angles; bias):\n", self, evaluation.score, evaluation.success);
+        for vector in &normalVectors {
+            report.push_str(&format!("    ({}; {:.3})\n", toDegrees(&vector[0..vector.len() - 1]), vector[vector.len() - 1]));
+        }
+        let misclassified: Vec<&(Vec<f64>, bool)> = self.points.iter()
+            .filter(|(point, correctClassification)| !SphereDiscreteNAProblem::isClassifiedCorrectly(point, *correctClassification, &normalVectors))
+            .collect();
+        let falseNegatives = misclassified.iter().filter(|(_, positive)| *positive).count();
+        report.push_str(&format!("Misclassified points: {} of {} ({} positives predicted negative, {} negatives predicted positive)\n",
+            misclassified.len(), self.points.len(), falseNegatives, misclassified.len() - falseNegatives));
+        for (point, positive) in misclassified {
+            report.push_str(&format!("    ({}) should be {}\n", toDegrees(point), if *positive { "positive" } else { "negative" }));
+        }
+        report
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,261 @@
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::experiment::{self, AlgorithmEnum, ExperimentConfig, IterationResult, ProblemEnum, QualityDiversity};
+use crate::mutationAlgorithms::crossover::Crossover;
+use crate::mutationAlgorithms::populationStrategy::Strategy;
+use crate::mutationAlgorithms::simulatedAnnealing::Cooling;
+use crate::mutationAlgorithms::restartStrategy::RestartMode;
+use crate::nn::activation::Activation;
+use crate::nn::ann::NANN;
+use crate::nn::networkFile::NetworkFormat;
+
+/*
+    Declarative description of a grid of experiments, loaded from a TOML or JSON file.
+    Every combination of problem, algorithm, strategy, network shape and resolution parameter is ran `repetitions` times,
+    and all results are written into a single file. Example (TOML):
+
+        output = "results.csv"
+        repetitions = 30
+        problems = ["Sphere Continuous Quarter", "Sphere Discrete 2D Half"]
+        algorithms = ["Harmonic (1+1)NA", "Self Adaptive (1+1)NA"]
+        strategies = ["(1+1)", "(5+10)", "(1,10)"]
+        networks = [[2, 1, 2], [2, 2, 2], [2, 4, 4, 2]]
+        r = [60, 120, 240]
+        hiddenActivation = "Binary Threshold"
+        workers = 8
+ */
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentSuite {
+    pub problems: Vec<String>,
+    pub algorithms: Vec<String>,
+    // Population strategies every algorithm is ran in, only "(1+1)" by default
+    #[serde(default = "defaultStrategies")]
+    pub strategies: Vec<String>,
+    // Crossover operator of a genetic algorithm every algorithm is ran in instead, see ExperimentConfig::crossover
+    pub crossover: Option<String>,
+    #[serde(default = "defaultPopulationSize")]
+    pub populationSize: usize,
+    #[serde(default = "defaultCrossoverRate")]
+    pub crossoverRate: f64,
+    // Network shapes in the form [input dimension, hidden layer widths..., output dimension]
+    pub networks: Vec<Vec<usize>>,
+    pub r: Vec<f64>,
+    pub repetitions: u32,
+    // Limits of every run, see ExperimentConfig
+    pub maxGenerations: Option<u64>,
+    pub maxEvaluations: Option<u64>,
+    pub maxTime: Option<f64>,
+    // Activation names of the hidden and output layers, Identity by default. The hidden activation can be a comma separated list
+    // with one activation per hidden layer, e.g. "ReLU,Tanh", which only fits networks with that many hidden layers
+    pub hiddenActivation: Option<String>,
+    pub outputActivation: Option<String>,
+    #[serde(default = "defaultOptimum")]
+    pub optimum: f64,
+    #[serde(default = "defaultNumberOfPoints")]
+    pub numberOfPoints: u32,
+    #[serde(default = "defaultSuccessAdaptation")]
+    pub successAdaptation: f64,
+    #[serde(default = "defaultFailureAdaptation")]
+    pub failureAdaptation: f64,
+    #[serde(default = "defaultBeta")]
+    pub beta: f64,
+    // Simulated annealing and random local search parameters, see ExperimentConfig. The schedule is "exponential", "linear" or "logarithmic"
+    #[serde(default = "defaultCoolingSchedule")]
+    pub coolingSchedule: Cooling,
+    #[serde(default = "defaultInitialTemperature")]
+    pub initialTemperature: f64,
+    #[serde(default = "defaultCoolingRate")]
+    pub coolingRate: f64,
+    // Probability that the (1+1)NA family switches a connection on or off instead of changing its weight, see ExperimentConfig
+    #[serde(default)]
+    pub connectionToggleProbability: f64,
+    #[serde(default = "defaultRestartPatience")]
+    pub restartPatience: u64,
+    // "reinitialise" or "perturb" to restart stalled runs, see ExperimentConfig
+    #[serde(default)]
+    pub restart: Option<RestartMode>,
+    // "map-elites" or "novelty-search" to run the algorithms in a quality-diversity runner, see ExperimentConfig
+    #[serde(default)]
+    pub qualityDiversity: Option<QualityDiversity>,
+    #[serde(default = "defaultArchiveCells")]
+    pub archiveCells: usize,
+    // Runs the algorithms in NSGA-II, see ExperimentConfig
+    #[serde(default)]
+    pub multiObjective: bool,
+    #[serde(default = "defaultBias")]
+    pub bias: bool,
+    // Number of threads the experiments are distributed across, one per CPU core by default
+    #[serde(default = "experiment::defaultWorkers")]
+    pub workers: usize,
+    // Master seed shared by every experiment in the suite. Drawn at random if not given
+    pub seed: Option<u64>,
+    pub output: Option<String>,
+    // Optional file recording every accepted mutation of every run, see ExperimentConfig::traceFile
+    pub trace: Option<String>,
+    // Optional file the archive of every quality-diversity run is exported to, see ExperimentConfig::archiveFile
+    pub archive: Option<String>,
+    // Optional file the Pareto front of every NSGA-II run is exported to, see ExperimentConfig::paretoFile
+    pub pareto: Option<String>,
+    // Optional directory the best network of every run is saved to, as "json" or "binary" (the default)
+    pub networkDirectory: Option<String>,
+    #[serde(default = "defaultNetworkFormat")]
+    pub networkFormat: NetworkFormat
+}
+
+fn defaultOptimum() -> f64 { ExperimentConfig::default().optimum }
+fn defaultNumberOfPoints() -> u32 { ExperimentConfig::default().numberOfPoints }
+fn defaultSuccessAdaptation() -> f64 { ExperimentConfig::default().successAdaptation }
+fn defaultFailureAdaptation() -> f64 { ExperimentConfig::default().failureAdaptation }
+fn defaultBeta() -> f64 { ExperimentConfig::default().beta }
+fn defaultCoolingSchedule() -> Cooling { ExperimentConfig::default().coolingSchedule }
+fn defaultInitialTemperature() -> f64 { ExperimentConfig::default().initialTemperature }
+fn defaultCoolingRate() -> f64 { ExperimentConfig::default().coolingRate }
+fn defaultRestartPatience() -> u64 { ExperimentConfig::default().restartPatience }
+fn defaultArchiveCells() -> usize { ExperimentConfig::default().archiveCells }
+fn defaultBias() -> bool { ExperimentConfig::default().bias }
+fn defaultStrategies() -> Vec<String> { vec![Strategy::onePlusOne().to_string()] }
+fn defaultPopulationSize() -> usize { ExperimentConfig::default().populationSize }
+fn defaultCrossoverRate() -> f64 { ExperimentConfig::default().crossoverRate }
+fn defaultNetworkFormat() -> NetworkFormat { ExperimentConfig::default().networkFormat }
+
+fn parseActivation(name: Option<&str>) -> Result<Activation, String> {
+    match name {
+        Some(name) => Activation::fromName(name).ok_or(format!("Unknown activation: {}", name)),
+        None => Ok(Activation::Identity)
+    }
+}
+
+impl ExperimentSuite {
+    // Reads a suite file. Files ending in .json are parsed as JSON, everything else as TOML.
+    pub fn load(path: &str) -> Result<ExperimentSuite, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Unable to read suite file {}: {}", path, e))?;
+        if path.to_lowercase().ends_with(".json") {
+            serde_json::from_str(&contents).map_err(|e| format!("Invalid suite file {}: {}", path, e))
+        } else {
+            toml::from_str(&contents).map_err(|e| format!("Invalid suite file {}: {}", path, e))
+        }
+    }
+
+    // Cartesian product of all listed parameters, in the order problems > algorithms > strategies > networks > r
+    pub fn expand(&self) -> Result<Vec<ExperimentConfig>, String> {
+        let mut configs = vec![];
+        let seed = self.seed.unwrap_or_else(|| ExperimentConfig::default().seed);
+        let hiddenActivations = experiment::parseActivations(self.hiddenActivation.as_deref().unwrap_or(""))?;
+        let outputActivation = parseActivation(self.outputActivation.as_deref())?;
+        let strategies = self.strategies.iter().map(|name| Strategy::fromName(name)).collect::<Result<Vec<Strategy>, String>>()?;
+        let crossover = match &self.crossover {
+            Some(name) => Some(Crossover::fromName(name).ok_or(format!("Unknown crossover: {}", name))?),
+            None => None
+        };
+        for problemName in &self.problems {
+            let problem = ProblemEnum::fromName(problemName).ok_or(format!("Unknown problem: {}", problemName))?;
+            for algorithmName in &self.algorithms {
+                let algorithm = AlgorithmEnum::fromName(algorithmName).ok_or(format!("Unknown mutation algorithm: {}", algorithmName))?;
+                for strategy in &strategies {
+                    for network in &self.networks {
+                        let (inputDim, hiddenDims, outputDim) = match network.as_slice() {
+                            [i, hidden @ .., o] => (*i, hidden.to_vec(), *o),
+                            _ => return Err(format!("Network shapes must have the form [input, hidden..., output]: {:?}", network))
+                        };
+                        let layerSizes: Vec<(usize, usize)> = network.windows(2).map(|w| (w[0], w[1])).collect();
+                        NANN::validateLayerSizes(&layerSizes).map_err(|e| format!("Invalid network shape {:?}: {}", network, e))?;
+                        for r in &self.r {
+                            let config = ExperimentConfig {
+                                problem: problem.clone(),
+                                algorithm,
+                                strategy: *strategy,
+                                crossover,
+                                populationSize: self.populationSize,
+                                crossoverRate: self.crossoverRate,
+                                r: *r,
+                                optimum: self.optimum,
+                                numberOfPoints: self.numberOfPoints,
+                                inputDim,
+                                hiddenDims: hiddenDims.clone(),
+                                outputDim,
+                                hiddenActivations: hiddenActivations.clone(),
+                                outputActivation,
+                                bias: self.bias,
+                                successAdaptation: self.successAdaptation,
+                                failureAdaptation: self.failureAdaptation,
+                                beta: self.beta,
+                                coolingSchedule: self.coolingSchedule,
+                                initialTemperature: self.initialTemperature,
+                                coolingRate: self.coolingRate,
+                                connectionToggleProbability: self.connectionToggleProbability,
+                                qualityDiversity: self.qualityDiversity,
+                                archiveCells: self.archiveCells,
+                                multiObjective: self.multiObjective,
+                                restart: self.restart,
+                                restartPatience: self.restartPatience,
+                                iterations: self.repetitions,
+                                maxGenerations: self.maxGenerations,
+                                maxEvaluations: self.maxEvaluations,
+                                maxTime: self.maxTime,
+                                firstIteration: 1,
+                                seed,
+                                workers: self.workers,
+                                cancelFlag: None,
+                                onProgress: None,
+                                outputFile: None,
+                                traceFile: self.trace.clone(),
+                                archiveFile: self.archive.clone(),
+                                paretoFile: self.pareto.clone(),
+                                networkDirectory: self.networkDirectory.clone(),
+                                networkFormat: self.networkFormat,
+                                initialNetwork: None
+                            };
+                            config.validate()?;
+                            configs.push(config);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(configs)
+    }
+}
+
+/*
+    Runs every experiment in the suite on `workers` threads, appending all results to `outputFile` if given,
+    every accepted mutation, archive and Pareto front to the experiments' trace, archive and Pareto front files if they have them.
+    Results are reported in the order of the expanded grid. `onIteration` receives the configuration of the experiment each result belongs to.
+ */
+pub fn runSuite(configs: &[ExperimentConfig], workers: usize, outputFile: Option<&str>, mut onIteration: impl FnMut(&ExperimentConfig, &IterationResult)) {
+    if let Some(outputFile) = outputFile {
+        experiment::createResultsFile(outputFile);
+    }
+    if let Some(traceFile) = configs.first().and_then(|config| config.traceFile.as_deref()) {
+        experiment::createTraceFile(traceFile);
+    }
+    if let Some(archiveFile) = configs.first().and_then(|config| config.archiveFile.as_deref()) {
+        experiment::createArchiveFile(archiveFile);
+    }
+    if let Some(paretoFile) = configs.first().and_then(|config| config.paretoFile.as_deref()) {
+        experiment::createParetoFile(paretoFile);
+    }
+    configs.iter().for_each(experiment::createNetworkDirectory);
+    let jobs: Vec<(&ExperimentConfig, u32)> = configs.iter()
+        .flat_map(|config| config.iterationRange().map(move |i| (config, i)))
+        .collect();
+    experiment::runJobs(&jobs, workers, |index, result| {
+        let config = jobs[index].0;
+        if let Some(outputFile) = outputFile {
+            experiment::appendResult(outputFile, &result, config);
+        }
+        if let Some(traceFile) = &config.traceFile {
+            experiment::appendTrace(traceFile, &result, config);
+        }
+        if let Some(archiveFile) = &config.archiveFile {
+            experiment::appendArchive(archiveFile, &result, config);
+        }
+        if let Some(paretoFile) = &config.paretoFile {
+            experiment::appendParetoFront(paretoFile, &result, config);
+        }
+        experiment::saveNetwork(&result, config);
+        onIteration(config, &result);
+    });
+}
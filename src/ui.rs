@@ -17,37 +17,72 @@
 
 // On Windows platform, don't show a console when opening the app.
 
-use std::fs::OpenOptions;
-use std::{fs::File, io::Write};
-
 use druid::text::ParseFormatter;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
 use druid::{
-    theme, Color, Data, Lens, RenderContext, Widget, WidgetExt,
+    theme, Color, Data, ExtEventSink, Lens, RenderContext, Widget, WidgetExt,
 };
-use druid::widget::{CrossAxisAlignment, Flex, Label, Scroll, Painter, TextBox, Stepper, SizedBox, MainAxisAlignment};
+use druid::widget::{Checkbox, CrossAxisAlignment, Flex, Label, Scroll, Painter, TextBox, Stepper, SizedBox, MainAxisAlignment};
 use druid_widget_nursery::DropdownSelect;
-use crate::mutationAlgorithms::{gaussianMutation::GaussianMutation, SALocalMutation::SALocalMutation, SAOnePlusOneNA::SAOnePlusOneNA, localOnePlusOneNA::LocalOnePlusOneNA, onePlusOneNA::OnePlusOneNA};
-use crate::nn::ann::{NANN, self};
-use crate::problems::cartpoleProblem::CartpoleProblem;
-use crate::problems::naProblem::NAProblem;
-use crate::problems::sphereContinuousProblem::SphereContinuousNAProblem;
-use crate::problems::sphereDiscreteProblem::SphereDiscreteNAProblem;
+use crate::experiment::{self, AlgorithmEnum, ExperimentConfig, ProblemEnum, QualityDiversity};
+use crate::mutationAlgorithms::crossover::Crossover;
+use crate::mutationAlgorithms::populationStrategy::Strategy;
+use crate::mutationAlgorithms::simulatedAnnealing::Cooling;
+use crate::mutationAlgorithms::restartStrategy::RestartMode;
+use crate::nn::activation::Activation;
+use crate::nn::networkFile::NetworkFormat;
+use crate::widgets::fitnessPlot::{FitnessCurves, FitnessPlot};
+use rand::Rng;
 #[derive(Clone, Data, Lens)]
 pub struct AppState {
     outputFile: String,
     algorithm: AlgorithmEnum,
+    // Population strategy, e.g. "(5+10)" or "(1,10)"
+    strategy: String,
+    // Crossover of the genetic algorithm the algorithm is ran in, None to run it on its own
+    crossover: Option<Crossover>,
+    // Quality-diversity runner the algorithm is ran in, None to run it on its own
+    qualityDiversity: Option<QualityDiversity>,
+    // Whether the algorithm is ran in NSGA-II
+    multiObjective: bool,
+    populationSize: f64,
     problem: ProblemEnum,
     r: f64,
     numberOfPoints: f64,
     inputDim: f64,
-    hiddenDim: f64,
+    // Comma separated hidden layer widths, e.g. "4,4,2"
+    hiddenLayers: String,
     outputDim: f64,
+    // Comma separated hidden layer activations, e.g. "ReLU,Tanh", or a single one for every hidden layer
+    hiddenActivations: String,
+    outputActivation: Activation,
     optimum: f64,
     bias: bool,
     successAdaptation: f64,
     failureAdaptation: f64,
+    beta: f64,
+    coolingSchedule: Cooling,
+    initialTemperature: f64,
+    connectionToggleProbability: f64,
+    // Restart mode of stalled runs, None to never restart
+    restart: Option<RestartMode>,
+    restartPatience: f64,
     iterations: f64,
-    results: String
+    // Optional budget of every iteration, left empty for no limit
+    maxEvaluations: String,
+    maxTime: String,
+    workers: f64,
+    seed: String,
+    results: String,
+    curves: FitnessCurves,
+    // Whether experiments are currently running on the background thread
+    running: bool,
+    // Raised by the Cancel button. A new flag is created for every batch of experiments
+    #[data(ignore)]
+    cancelFlag: Arc<AtomicBool>
 }
 
 impl AppState {
@@ -55,18 +90,38 @@ impl AppState {
         AppState {
             outputFile: "".to_string(),
             algorithm: AlgorithmEnum::OnePlusOneNA,
+            strategy: Strategy::onePlusOne().to_string(),
+            crossover: None,
+            qualityDiversity: None,
+            multiObjective: false,
+            populationSize: 20.0,
             problem: ProblemEnum::SphereContinuous("Sphere Continuous Quarter".to_string()),
             r: 120.0,
             numberOfPoints: 1000.0,
             inputDim: 2.0,
-            hiddenDim: 1.0,
+            hiddenLayers: "1".to_string(),
             outputDim: 2.0,
+            hiddenActivations: "Identity".to_string(),
+            outputActivation: Activation::Identity,
             optimum: 1.0,
             bias: true,
             successAdaptation: 1.7,
             failureAdaptation: 0.9,
+            beta: 1.5,
+            coolingSchedule: Cooling::Exponential,
+            initialTemperature: 0.05,
+            connectionToggleProbability: 0.0,
+            restart: None,
+            restartPatience: 1000.0,
             results: "".to_string(),
-            iterations: 1.0
+            iterations: 1.0,
+            maxEvaluations: "".to_string(),
+            maxTime: "".to_string(),
+            workers: experiment::defaultWorkers() as f64,
+            seed: "".to_string(),
+            curves: Arc::new(vec![]),
+            running: false,
+            cancelFlag: Arc::new(AtomicBool::new(false))
         }
     }
 
@@ -77,7 +132,76 @@ impl AppState {
 
     fn clearResults(&mut self) {
         self.results = "".to_string();
+        self.curves = Arc::new(vec![]);
+    }
+
+    // Appends a point to an iteration's curve. A new vector is created so the plot sees the change
+    fn addCurvePoint(&mut self, index: usize, generation: f64, score: f64) {
+        let mut curves = (*self.curves).clone();
+        if curves.len() <= index {
+            curves.resize(index + 1, vec![]);
+        }
+        curves[index].push((generation, score));
+        self.curves = Arc::new(curves);
+    }
+
+    fn toConfig(&self) -> Result<ExperimentConfig, String> {
+        let config = ExperimentConfig {
+            problem: self.problem.clone(),
+            algorithm: self.algorithm,
+            strategy: Strategy::fromName(&self.strategy)?,
+            crossover: self.crossover,
+            populationSize: self.populationSize as usize,
+            crossoverRate: ExperimentConfig::default().crossoverRate,
+            r: self.r,
+            optimum: self.optimum,
+            numberOfPoints: self.numberOfPoints as u32,
+            inputDim: self.inputDim as usize,
+            hiddenDims: experiment::parseHiddenDims(&self.hiddenLayers)?,
+            outputDim: self.outputDim as usize,
+            hiddenActivations: experiment::parseActivations(&self.hiddenActivations)?,
+            outputActivation: self.outputActivation,
+            bias: self.bias,
+            successAdaptation: self.successAdaptation,
+            failureAdaptation: self.failureAdaptation,
+            beta: self.beta,
+            coolingSchedule: self.coolingSchedule,
+            initialTemperature: self.initialTemperature,
+            coolingRate: ExperimentConfig::default().coolingRate,
+            connectionToggleProbability: self.connectionToggleProbability,
+            qualityDiversity: self.qualityDiversity,
+            archiveCells: ExperimentConfig::default().archiveCells,
+            multiObjective: self.multiObjective,
+            restart: self.restart,
+            restartPatience: self.restartPatience as u64,
+            iterations: self.iterations as u32,
+            maxGenerations: None,
+            maxEvaluations: parseOptional(&self.maxEvaluations, "Max Evaluations")?,
+            maxTime: parseOptional(&self.maxTime, "Time Limit")?,
+            workers: self.workers as usize,
+            firstIteration: 1,
+            seed: parseOptional(&self.seed, "Seed")?.unwrap_or_else(|| rand::thread_rng().gen()),
+            cancelFlag: Some(self.cancelFlag.clone()),
+            onProgress: None,
+            outputFile: if self.outputFile != "" { Some(self.outputFile.clone()) } else { None },
+            traceFile: None,
+            archiveFile: None,
+            paretoFile: None,
+            networkDirectory: None,
+            networkFormat: NetworkFormat::Binary,
+            initialNetwork: None
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+// Empty text boxes mean the value isn't set
+fn parseOptional<T: std::str::FromStr>(text: &str, name: &str) -> Result<Option<T>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
     }
+    text.trim().parse().map(Some).map_err(|_| format!("Invalid value for {}: {}", name, text))
 }
 
 fn make_submit_button() -> impl Widget<AppState> {
@@ -99,8 +223,31 @@ fn make_submit_button() -> impl Widget<AppState> {
         .center()
         .background(painter)
         .fix_height(40.0).fix_width(250.0)
-        .on_click(move |_ctx, data: &mut AppState, _env| runUIExperiments(data)
-    )
+        .on_click(move |ctx, data: &mut AppState, _env| runUIExperiments(ctx.get_external_handle(), data))
+        .disabled_if(|data: &AppState, _env| data.running)
+}
+
+fn make_cancel_button() -> impl Widget<AppState> {
+    let painter = Painter::new(|ctx, _, env| {
+        let bounds = ctx.size().to_rect();
+
+        ctx.fill(bounds, &env.get(theme::BACKGROUND_LIGHT));
+        if ctx.is_hot() {
+            ctx.stroke(bounds.inset(-0.5), &Color::WHITE, 1.0);
+        }
+
+        if ctx.is_active() {
+            ctx.fill(bounds, &Color::rgb8(0x71, 0x71, 0x71));
+        }
+    });
+
+    Label::new("Cancel")
+        .with_text_size(18.)
+        .center()
+        .background(painter)
+        .fix_height(40.0).fix_width(120.0)
+        .on_click(move |_ctx, data: &mut AppState, _env| data.cancelFlag.store(true, Ordering::Relaxed))
+        .disabled_if(|data: &AppState, _env| !data.running)
 }
 
 fn digit_button(digit: u8) -> impl Widget<AppState> {
@@ -122,7 +269,7 @@ fn digit_button(digit: u8) -> impl Widget<AppState> {
         .with_text_size(24.)
         .center()
         .background(painter)
-        .on_click(move |_ctx, data: &mut AppState, _env| runUIExperiments(data))
+        .on_click(move |ctx, data: &mut AppState, _env| runUIExperiments(ctx.get_external_handle(), data))
 }
 
 fn flex_row<T: Data>(
@@ -139,25 +286,7 @@ fn make_problem_row() -> Flex<AppState> {
     Flex::column().cross_axis_alignment(CrossAxisAlignment::Center).with_child(
         Flex::row().cross_axis_alignment(CrossAxisAlignment::Center).with_child(Label::new("Problem Parameters").with_text_size(18.))
     ).with_spacer(6.0).with_child(
-        Flex::row().cross_axis_alignment(CrossAxisAlignment::Start).with_child(Label::new("Problem: ")).with_child(DropdownSelect::new(vec![
-            ("Sphere Continuous Quarter", ProblemEnum::SphereContinuous("Sphere Continuous Quarter".to_string())),
-            ("Sphere Continuous Half", ProblemEnum::SphereContinuous("Sphere Continuous Half".to_string())),
-            ("Sphere Continuous Two Quarters", ProblemEnum::SphereContinuous("Sphere Continuous Two Quarters".to_string())),
-            ("Sphere Continuous Local Optima", ProblemEnum::SphereContinuous("Sphere Continuous Local Optima".to_string())),
-            ("Sphere Discrete 2D Quarter", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Quarter".to_string())),
-            ("Sphere Discrete 2D Half", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Half".to_string())),
-            ("Sphere Discrete 2D Two Quarters", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Two Quarters".to_string())),
-            ("Sphere Discrete 2D Local Optima", ProblemEnum::SphereDiscrete("Sphere Discrete 2D Local Optima".to_string())),
-            ("Sphere 3D Corner", ProblemEnum::SphereDiscrete("Sphere 3D Corner".to_string())),
-            ("Sphere 3D Half", ProblemEnum::SphereDiscrete("Sphere 3D Half".to_string())),
-            ("Sphere 3D Slice", ProblemEnum::SphereDiscrete("Sphere 3D Slice".to_string())),
-            ("Sphere 3D Two Slices", ProblemEnum::SphereDiscrete("Sphere 3D Two Slices".to_string())),
-            ("Sphere 4D Quarter", ProblemEnum::SphereDiscrete("Sphere 4D Quarter".to_string())),
-            ("Sphere 4D Half", ProblemEnum::SphereDiscrete("Sphere 4D Half".to_string())),
-            ("Sphere 4D Two Quarters", ProblemEnum::SphereDiscrete("Sphere 4D Two Quarters".to_string())),
-            ("Cartpole N Steps", ProblemEnum::Cartpole("N Steps".to_string())),
-            ("Cartpole Discrete", ProblemEnum::Cartpole("Cartpole Discrete".to_string())),
-        ])
+        Flex::row().cross_axis_alignment(CrossAxisAlignment::Start).with_child(Label::new("Problem: ")).with_child(DropdownSelect::new(ProblemEnum::all())
         .align_left()
         .lens(AppState::problem))
         .with_spacer(16.0).with_child(Flex::row()
@@ -192,15 +321,11 @@ fn make_alg_row() -> Flex<AppState> {
     Flex::column().cross_axis_alignment(CrossAxisAlignment::Center).with_child(
         Flex::row().cross_axis_alignment(CrossAxisAlignment::Center).with_child(Label::new("Algorithm Parameters").with_text_size(18.))
     ).with_spacer(6.0).with_child(
-        Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(Label::new("Mutation Algorithm: ")).with_child(DropdownSelect::new(vec![
-            ("Harmonic (1+1)NA", AlgorithmEnum::OnePlusOneNA),
-            ("Gaussian", AlgorithmEnum::GaussianMutation),
-            ("Local (1+1)NA", AlgorithmEnum::LocalOnePlusOneNA),
-            ("Self Adaptive (1+1)NA", AlgorithmEnum::SAOnePlusOneNA),
-            ("Self Adaptive Local", AlgorithmEnum::SALocalMutation),
-        ])
+        Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(Label::new("Mutation Algorithm: ")).with_child(DropdownSelect::new(AlgorithmEnum::all())
         .align_left()
-        .lens(AppState::algorithm)).with_spacer(36.0)
+        .lens(AppState::algorithm)).with_spacer(16.0)
+        .with_child(Label::new("Strategy: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+        .with_child(TextBox::new().with_placeholder("(1+1)").fix_width(72.0).lens(AppState::strategy)).with_spacer(36.0)
         .with_child(Flex::column().with_child(Label::new("Self Adaptation Parameters")).with_spacer(6.0).with_child(Flex::row().with_child(
             Label::new("Success multiplier: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
             .with_child(Flex::row()
@@ -229,10 +354,111 @@ fn make_alg_row() -> Flex<AppState> {
                     )
             ).disabled_if(|appState, env| !matches!(appState.algorithm, AlgorithmEnum::SALocalMutation) && !matches!(appState.algorithm, AlgorithmEnum::SAOnePlusOneNA)
             )
+        )).with_spacer(36.0)
+        .with_child(Flex::column().with_child(Label::new("Fast Mutation Parameters")).with_spacer(6.0).with_child(Flex::row().with_child(
+            Label::new("Power-law exponent: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+            .with_child(
+                Stepper::new()
+                    .with_range(0.0, 4.0)
+                    .with_step(0.1)
+                    .lens(AppState::beta),
+            ).with_child(
+                Label::new(|data: &f64, _env: &_| format!("{:.1}", data))
+                .lens(AppState::beta)
+                .fix_width(48.0)
+            ).disabled_if(|appState, _env| !appState.algorithm.usesPowerLaw())
+        )).with_spacer(36.0)
+        .with_child(Flex::column().with_child(Label::new("Sparsity")).with_spacer(6.0).with_child(Flex::row().with_child(
+            Label::new("Toggle probability: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+            .with_child(
+                Stepper::new()
+                    .with_range(0.0, 1.0)
+                    .with_step(0.05)
+                    .lens(AppState::connectionToggleProbability),
+            ).with_child(
+                Label::new(|data: &f64, _env: &_| format!("{:.2}", data))
+                .lens(AppState::connectionToggleProbability)
+                .fix_width(48.0)
+            ).disabled_if(|appState, _env| !appState.algorithm.togglesConnections())
         ))
+    ).with_spacer(8.0).with_child(
+        Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(Label::new("Genetic Algorithm Crossover: ")).with_child(DropdownSelect::new(crossoverOptions())
+        .align_left()
+        .lens(AppState::crossover)).with_spacer(16.0)
+        .with_child(Label::new("Quality Diversity: ")).with_child(DropdownSelect::new(qualityDiversityOptions())
+        .align_left()
+        .lens(AppState::qualityDiversity)).with_spacer(16.0)
+        .with_child(Checkbox::new("NSGA-II").lens(AppState::multiObjective)).with_spacer(16.0)
+        .with_child(Flex::row().with_child(
+            Label::new("Population Size: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+            .with_child(
+                Stepper::new()
+                    .with_range(2.0, 1000.0)
+                    .with_step(1.0)
+                    .lens(AppState::populationSize),
+            ).with_child(
+                Label::new(|data: &f64, _env: &_| data.to_string().clone())
+                .lens(AppState::populationSize)
+                .fix_width(48.0)
+            ).disabled_if(|appState, _env| appState.crossover.is_none() && appState.qualityDiversity != Some(QualityDiversity::NoveltySearch) && !appState.multiObjective)
+        )
+    ).with_spacer(8.0).with_child(
+        Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(Flex::row()
+            .with_child(Label::new("Annealing Cooling: ")).with_child(DropdownSelect::new(Cooling::all())
+            .align_left()
+            .lens(AppState::coolingSchedule)).with_spacer(16.0)
+            .with_child(Label::new("Initial Temperature: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+            .with_child(
+                Stepper::new()
+                    .with_range(0.01, 1.0)
+                    .with_step(0.01)
+                    .lens(AppState::initialTemperature),
+            ).with_child(
+                Label::new(|data: &f64, _env: &_| format!("{:.2}", data))
+                .lens(AppState::initialTemperature)
+                .fix_width(48.0)
+            ).disabled_if(|appState, _env| appState.algorithm != AlgorithmEnum::SimulatedAnnealing)
+        ).with_spacer(36.0)
+        .with_child(Label::new("Restarts: ")).with_child(DropdownSelect::new(restartOptions())
+            .align_left()
+            .lens(AppState::restart)).with_spacer(16.0)
+        .with_child(Flex::row().with_child(
+            Label::new("Restart Patience: ")).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING)
+            .with_child(
+                Stepper::new()
+                    .with_range(100.0, 100000.0)
+                    .with_step(100.0)
+                    .lens(AppState::restartPatience),
+            ).with_child(
+                Label::new(|data: &f64, _env: &_| data.to_string().clone())
+                .lens(AppState::restartPatience)
+                .fix_width(64.0)
+            ).disabled_if(|appState, _env| appState.algorithm != AlgorithmEnum::RandomLocalSearch && appState.restart.is_none())
+        )
     )
 }
 
+// "None" runs the mutation algorithm on its own
+fn qualityDiversityOptions() -> Vec<(&'static str, Option<QualityDiversity>)> {
+    let mut options = vec![("None", None)];
+    options.extend(QualityDiversity::all().into_iter().map(|(name, qualityDiversity)| (name, Some(qualityDiversity))));
+    options
+}
+
+// "None" never restarts
+fn restartOptions() -> Vec<(&'static str, Option<RestartMode>)> {
+    let mut options = vec![("None", None)];
+    options.extend(RestartMode::all().into_iter().map(|(name, mode)| (name, Some(mode))));
+    options
+}
+
+// "None" runs the mutation algorithm on its own
+fn crossoverOptions() -> Vec<(&'static str, Option<Crossover>)> {
+    let mut options = vec![("None", None)];
+    options.extend(Crossover::all().into_iter().map(|(name, crossover)| (name, Some(crossover))));
+    options
+}
+
 fn make_general_row() -> Flex<AppState> {
     Flex::row().cross_axis_alignment(CrossAxisAlignment::Start).with_child(Flex::column()
     .with_child(
@@ -252,31 +478,41 @@ fn make_general_row() -> Flex<AppState> {
                 .fix_width(12.0)
             ).with_spacer(16.0)
         ).with_child(
-            Label::new("Hidden Dimension: ")
+            Label::new("Hidden Layers: ")
+        ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(Flex::row()
+            .with_child(
+                TextBox::new().with_placeholder("e.g. 4,4,2").fix_width(72.0).lens(AppState::hiddenLayers)
+            ).with_spacer(16.0)
+        ).with_child(
+            Label::new("Output Dimension: ")
         ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(Flex::row()
             .with_child(
                 Stepper::new()
                     .with_range(1.0, 50.0)
                     .with_step(1.0)
-                    .lens(AppState::hiddenDim),
+                    .lens(AppState::outputDim),
             ).with_child(
                 Label::new(|data: &f64, _env: &_| data.to_string().clone())
-                    .lens(AppState::hiddenDim)
+                    .lens(AppState::outputDim)
                     .fix_width(12.0)
             ).with_spacer(16.0)
         ).with_child(
-            Label::new("Output Dimension: ")
+            Label::new("Workers: ")
         ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(Flex::row()
             .with_child(
                 Stepper::new()
-                    .with_range(1.0, 50.0)
+                    .with_range(1.0, 64.0)
                     .with_step(1.0)
-                    .lens(AppState::outputDim),
+                    .lens(AppState::workers),
             ).with_child(
                 Label::new(|data: &f64, _env: &_| data.to_string().clone())
-                    .lens(AppState::outputDim)
+                    .lens(AppState::workers)
                     .fix_width(12.0)
             ).with_spacer(16.0)
+        ).with_child(
+            Label::new("Seed: ")
+        ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(
+            TextBox::new().with_placeholder("Random").fix_width(120.0).lens(AppState::seed)
         )
     ).with_spacer(8.0).with_child(Flex::row()
         .with_child(
@@ -311,6 +547,25 @@ fn make_general_row() -> Flex<AppState> {
         ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(
             TextBox::new().with_placeholder("Leave empty to not export results").fix_width(250.0).lens(AppState::outputFile)
         )
+    ).with_spacer(8.0).with_child(Flex::row()
+        .with_child(
+            Label::new("Hidden Activations: ")
+        ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(
+            TextBox::new().with_placeholder("Identity").fix_width(144.0).lens(AppState::hiddenActivations)
+        ).with_spacer(16.0).with_child(
+            Label::new("Output Activation: ")
+        ).with_child(DropdownSelect::new(Activation::all())
+            .align_left()
+            .lens(AppState::outputActivation)
+        ).with_spacer(16.0).with_child(
+            Label::new("Max Evaluations: ")
+        ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(
+            TextBox::new().with_placeholder("No limit").fix_width(96.0).lens(AppState::maxEvaluations)
+        ).with_spacer(16.0).with_child(
+            Label::new("Time Limit (s): ")
+        ).with_spacer(druid::theme::WIDGET_CONTROL_COMPONENT_PADDING).with_child(
+            TextBox::new().with_placeholder("No limit").fix_width(72.0).lens(AppState::maxTime)
+        )
     )))
 }
 
@@ -318,14 +573,16 @@ pub fn build_ui() -> impl Widget<AppState> {
     let mut generalRow = make_general_row();
     let problemRow = make_problem_row();
     let algRow = make_alg_row();
-    let submitRow = Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(make_submit_button());
+    let submitRow = Flex::row().main_axis_alignment(MainAxisAlignment::Center).cross_axis_alignment(CrossAxisAlignment::Center).with_child(make_submit_button()).with_spacer(16.0).with_child(make_cancel_button());
     let resultRow = Flex::row().cross_axis_alignment(CrossAxisAlignment::Start).with_flex_child(Scroll::new(SizedBox::new(
         Label::new(|data: &String, _env: &_| data.clone())
         .with_text_size(16.0).with_text_color(Color::BLACK)
         .lens(AppState::results)
         .padding(5.0)
         .background(Color::WHITE)
-    ).expand()), 1.0).must_fill_main_axis(true).expand().background(Color::WHITE);
+    ).expand()), 1.0)
+    .with_flex_child(FitnessPlot::new().lens(AppState::curves).expand(), 1.0)
+    .must_fill_main_axis(true).expand().background(Color::WHITE);
     return Flex::column().must_fill_main_axis(true).with_spacer(24.0)
         .with_child(generalRow).with_spacer(8.0)
         .with_child(problemRow).with_spacer(8.0)
@@ -334,148 +591,58 @@ pub fn build_ui() -> impl Widget<AppState> {
         .with_flex_child(resultRow, 1.0);
 }
 
-fn runUIExperiments(data: &mut AppState) {
-    AppState::clearResults(data);
-    let mut problemString = "Problem: ".to_string();
-    match &data.problem {
-        ProblemEnum::SphereDiscrete(x) => {
-            problemString.push_str(x);
-            problemString.push_str(", Number of Points: ");
-            problemString.push_str(&data.numberOfPoints.to_string());
-        },
-        ProblemEnum::SphereContinuous(x) => {
-            problemString.push_str(x);
-        },
-        ProblemEnum::Cartpole(x) => {
-            problemString.push_str(x);
-        },
+/*
+    Runs the experiments on a background thread so the window stays responsive.
+    Each finished iteration is pushed back into the application state through `sink`.
+ */
+fn runUIExperiments(sink: ExtEventSink, data: &mut AppState) {
+    if data.running {
+        return;
     }
-    let mut algorithmString = "Mutation Algorithm: ".to_string();
-    match &data.algorithm {
-        AlgorithmEnum::SALocalMutation => {
-            algorithmString.push_str("Self Adaptive Local");
-            algorithmString.push_str(", Success Multiplier: ");
-            algorithmString.push_str(&data.successAdaptation.to_string());
-            algorithmString.push_str(", Failure Multiplier: ");
-            algorithmString.push_str(&data.failureAdaptation.to_string());
-        },
-        AlgorithmEnum::SAOnePlusOneNA => {
-            algorithmString.push_str("Self Adaptive (1+1)NA");
-        },
-        AlgorithmEnum::GaussianMutation => {
-            algorithmString.push_str("Gaussian");
-        },
-        AlgorithmEnum::OnePlusOneNA => {
-            algorithmString.push_str("(1+1)NA");
-        },
-        AlgorithmEnum::LocalOnePlusOneNA => {
-            algorithmString.push_str("Local (1+1)NA");
+    AppState::clearResults(data);
+    data.cancelFlag = Arc::new(AtomicBool::new(false));
+    let mut config = match data.toConfig() {
+        Ok(config) => config,
+        Err(message) => {
+            AppState::addResults(data, message);
+            return;
         }
+    };
+    data.running = true;
+    let firstIteration = config.firstIteration;
+    let progressSink = sink.clone();
+    config.onProgress = Some(Arc::new(move |iteration, generation, bestScore| {
+        progressSink.add_idle_callback(move |data: &mut AppState| {
+            data.addCurvePoint((iteration - firstIteration) as usize, generation as f64, bestScore);
+        });
+    }));
+    for line in config.describe() {
+        AppState::addResults(data, line);
     }
-    let networkString = format!("Inputs: {}, Hidden Neurons: {}, Outputs: {}, Resolution: {}, Optimum: {}\n",
-        data.inputDim.to_string(),
-        data.hiddenDim.to_string(),
-        data.outputDim.to_string(),
-        data.r.to_string(),
-        data.optimum.to_string()
-    );
-    AppState::addResults(data, format!("Running {} experiments with parameters:", data.iterations));
-    AppState::addResults(data, problemString.to_string());
-    AppState::addResults(data, algorithmString.to_string());
-    AppState::addResults(data, networkString);
-    
-    unsafe { crate::R = data.r };
-    unsafe { crate::OPTIMUM = data.optimum };
-    if data.outputFile != "" {
-        let mut resultFile = File::create(&data.outputFile).expect("Unable to created final result file");
-        writeln!(resultFile, "R,Mutation Algorithm,Problem,Input Dim,Hidden Dim,Output Dim,Iteration,Generation,Score,Solution").expect("Failed writing result file");
-    }
-    for i in 1..data.iterations as i32 + 1 {
-        // [(inputs, number of hidden neurons) (number of hidden neurons, number of hidden neurons), (number of hidden neurons, outputs)]
-        let nn = NANN::new(vec![(data.inputDim as usize, data.hiddenDim as usize), (data.hiddenDim as usize, data.outputDim as usize)], |x| x, data.r, true);
-        let problem: Box<dyn NAProblem> = match &data.problem {
-            ProblemEnum::SphereDiscrete(x) => {
-                match x.as_str() {
-                    "Sphere Discrete 2D Quarter" => SphereDiscreteNAProblem::newQuarter(data.numberOfPoints as u32),
-                    "Sphere Discrete 2D Half" => SphereDiscreteNAProblem::newHalf(data.numberOfPoints as u32),
-                    "Sphere Discrete 2D Two Quarters" => SphereDiscreteNAProblem::newTwoQuarters(data.numberOfPoints as u32),
-                    "Sphere Discrete 2D Local Optima" => SphereDiscreteNAProblem::newLocalOpt(data.numberOfPoints as u32),
-                    "Sphere 3D Corner" => SphereDiscreteNAProblem::newCorner3D(data.numberOfPoints as u32),
-                    "Sphere 3D Half" => SphereDiscreteNAProblem::newHalf3D(data.numberOfPoints as u32),
-                    "Sphere 3D Slice" => SphereDiscreteNAProblem::newSlice3D(data.numberOfPoints as u32),
-                    "Sphere 3D Two Slices" => SphereDiscreteNAProblem::newTwoSlices3D(data.numberOfPoints as u32),
-                    "Sphere 4D Quarter" => SphereDiscreteNAProblem::newQuarter4D(data.numberOfPoints as u32),
-                    "Sphere 4D Half" => SphereDiscreteNAProblem::newHalf4D(data.numberOfPoints as u32),
-                    "Sphere 4D Two Quarters" => SphereDiscreteNAProblem::newTwoQuarters4D(data.numberOfPoints as u32),
-                    _ => SphereDiscreteNAProblem::newQuarter(data.numberOfPoints as u32),
-                }
-            },
-            ProblemEnum::SphereContinuous(x) => {
-                match x.as_str() {
-                    "Sphere Continuous Quarter" => SphereContinuousNAProblem::newQuarter(),
-                    "Sphere Continuous Half" => SphereContinuousNAProblem::newHalf(),
-                    "Sphere Continuous Two Quarters" => SphereContinuousNAProblem::newTwoQuarters(),
-                    "Sphere Continuous Local Optima" => SphereContinuousNAProblem::newLocalOpt(),
-                    _ => SphereContinuousNAProblem::newQuarter()
+    thread::spawn(move || {
+        experiment::runExperiments(&config, |result| {
+            let solutionString: String = result.evaluation.solution().map(|x| ((x*1000.0).round()/1000.0).to_string()).into_raw_vec().join("; ");
+            let mut lines = vec![
+                format!("Iteration {}/{}: finished in generation {} ({} evaluations) with a score of {}{}", result.iteration, config.iterations, result.generation, result.evaluations, (result.evaluation.score*1000.0).round()/1000.0, result.restartsSummary()),
+                format!("Solution found: [{solutionString}]")
+            ];
+            let objectives: Vec<String> = result.evaluation.objectives.iter().map(|(name, value)| format!("{}: {}", name, (value*1000.0).round()/1000.0)).collect();
+            lines.push(format!("Evaluation steps: {}{}{}", result.evaluation.steps, if objectives.is_empty() { "" } else { ", " }, objectives.join(", ")));
+            let (index, generation) = ((result.iteration - firstIteration) as usize, result.generation as f64);
+            sink.add_idle_callback(move |data: &mut AppState| {
+                lines.into_iter().for_each(|line| AppState::addResults(data, line));
+                // Extends the curve until the final generation, keeping the last best score
+                if let Some(&(_, bestScore)) = data.curves.get(index).and_then(|curve| curve.last()) {
+                    data.addCurvePoint(index, generation, bestScore);
                 }
-            },
-            ProblemEnum::Cartpole(x) => {
-                match x.as_str() {
-                    "Cartpole N Steps" => CartpoleProblem::newNSteps(),
-                    "Cartpole Discrete" => CartpoleProblem::newDiscretizedContinuous(),
-                    _ => CartpoleProblem::newNSteps(),
-                }
-            },
-        };
-        let mutationAlgorithm = match &data.algorithm {
-            AlgorithmEnum::SALocalMutation => {
-                SALocalMutation::new(&nn, problem, data.r, data.successAdaptation, data.failureAdaptation)
-            },
-            AlgorithmEnum::SAOnePlusOneNA => {
-                SAOnePlusOneNA::new(&nn, problem, data.r, data.successAdaptation, data.failureAdaptation)
-            },
-            AlgorithmEnum::GaussianMutation => {
-                GaussianMutation::new(problem)
-            },
-            AlgorithmEnum::OnePlusOneNA => {
-                OnePlusOneNA::new(&nn, problem, data.r)
-            },
-            AlgorithmEnum::LocalOnePlusOneNA => {
-                LocalOnePlusOneNA::new(&nn, problem, data.r)
+            });
+        });
+        let cancelled = config.isCancelled();
+        sink.add_idle_callback(move |data: &mut AppState| {
+            if cancelled {
+                AppState::addResults(data, "Experiments cancelled".to_string());
             }
-        };
-            
-        let problemName = mutationAlgorithm.getProblem().to_string();
-        let mutationAlgorithmName = mutationAlgorithm.to_string();
-        let (generation, _, score, solution) = ann::run(
-            nn,
-            mutationAlgorithm
-        );
-        
-        let solutionString: String = solution.map(|x| ((x*1000.0).round()/1000.0).to_string()).into_raw_vec().join("; ");
-        AppState::addResults(data, format!("Iteration {i}/{}: finished in generation {generation} with a score of {}", data.iterations, (score*1000.0).round()/1000.0));
-        AppState::addResults(data, format!("Solution found: [{solutionString}]"));
-        if data.outputFile != "" {
-            let mut resultFile = OpenOptions::new().append(true).open(&data.outputFile).unwrap();
-            let exportSolutionString: String = solution.map(|x| x.to_string()).into_raw_vec().join(";");
-            writeln!(resultFile, "{},{},{},{},{},{},{},{},{},{}",
-                data.r, mutationAlgorithmName, problemName, data.inputDim, data.hiddenDim, data.outputDim, i, generation, score, exportSolutionString
-            ).expect("Failed writing result file");
-        }
-        };
-}
-
-#[derive(Data, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
-enum AlgorithmEnum {
-    LocalOnePlusOneNA,
-    GaussianMutation,
-    OnePlusOneNA,
-    SALocalMutation,
-    SAOnePlusOneNA
+            data.running = false;
+        });
+    });
 }
-#[derive(Data, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
-enum ProblemEnum {
-    SphereDiscrete(String),
-    SphereContinuous(String),
-    Cartpole(String),
-}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// Limits on a single run. The run stops as soon as any of the limits that are set is reached
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budget {
+    pub maxGenerations: Option<u64>,
+    pub maxEvaluations: Option<u64>,
+    // Wall-clock time
+    pub maxTime: Option<Duration>
+}
+
+impl Budget {
+    pub fn isUnlimited(&self) -> bool {
+        self.maxGenerations.is_none() && self.maxEvaluations.is_none() && self.maxTime.is_none()
+    }
+
+    pub fn isExhausted(&self, generations: u64, evaluations: u64, elapsed: Duration) -> bool {
+        self.maxGenerations.map_or(false, |max| generations >= max)
+            || self.maxEvaluations.map_or(false, |max| evaluations >= max)
+            || self.maxTime.map_or(false, |max| elapsed >= max)
+    }
+}
+
+/*
+    Per-run values that used to be global: the resolution parameter and the optimum at which a run is considered successful.
+    Passed explicitly to problems, mutation algorithms and the run loop, so experiments with different parameters can run side by side.
+ */
+#[derive(Debug, Clone)]
+pub struct ExperimentContext {
+    pub resolution: f64,
+    pub optimum: f64,
+    // When set, runs stop between generations once the flag is raised
+    pub cancelFlag: Option<Arc<AtomicBool>>,
+    pub budget: Budget
+}
+
+impl ExperimentContext {
+    pub fn new(resolution: f64, optimum: f64) -> ExperimentContext {
+        ExperimentContext {
+            resolution,
+            optimum,
+            cancelFlag: None,
+            budget: Budget {
+                maxGenerations: Some(ExperimentContext::defaultMaxGenerations(resolution)),
+                ..Budget::default()
+            }
+        }
+    }
+
+    // Replaces the default generation limit, unless `budget` sets no limit at all
+    pub fn withBudget(mut self, budget: Budget) -> ExperimentContext {
+        if !budget.isUnlimited() {
+            self.budget = budget;
+        }
+        self
+    }
+
+    pub fn withCancelFlag(mut self, cancelFlag: Option<Arc<AtomicBool>>) -> ExperimentContext {
+        self.cancelFlag = cancelFlag;
+        self
+    }
+
+    pub fn isCancelled(&self) -> bool {
+        self.cancelFlag.as_ref().map_or(false, |flag| flag.load(Ordering::Relaxed))
+    }
+
+    // A score counts as a success if it's within one step of the resolution grid from the optimum
+    pub fn isSuccess(&self, score: f64) -> bool {
+        score >= self.optimum - (1.0 / self.resolution)
+    }
+
+    // Generation limit for a single run when no budget is given, 100 * R * log2(R)
+    pub fn defaultMaxGenerations(resolution: f64) -> u64 {
+        (100.0 * resolution * resolution.log2()).max(0.0) as u64
+    }
+}
@@ -25,12 +25,11 @@ pub fn ring(value: f64, limit: f64, allowNegativeResults: bool) -> f64 {
 }
 
 // Generates points of the specified dimension using a method proposed by Muller and Marsaglia
-pub fn nSpherePointGeneration(numberOfPoints: u32, dimensions: usize) -> Vec<Vec<f64>> {
-    let mut randGen = rand::thread_rng();
+pub fn nSpherePointGeneration(numberOfPoints: u32, dimensions: usize, randGen: &mut impl Rng) -> Vec<Vec<f64>> {
     let gaussGenerator = Normal::<f64>::new(0.0, 1.0).unwrap();
     let mut points: Vec<Vec<f64>> = vec![];
     for _ in 0..numberOfPoints {
-        let generatedPoint: Vec<f64> = gaussGenerator.sample_iter(&mut randGen).take(dimensions).collect();
+        let generatedPoint: Vec<f64> = gaussGenerator.sample_iter(&mut *randGen).take(dimensions).collect();
         let sum = generatedPoint.iter().map(|p| p.powi(2)).sum::<f64>().sqrt();
         points.push(generatedPoint.iter().map(|x| x/sum).collect());
     }
@@ -120,9 +119,9 @@ pub fn harmonicNumber(n: f64) -> f64 {
 }
 
 // Generates a value according to the Harmonic Distribution
-pub fn harmonicDistribution() -> f64 {
-    let harmNumber = unsafe { harmonicNumber(crate::R) };
-    let u: f64 = rand::thread_rng().gen();
+pub fn harmonicDistribution(resolution: f64, randGen: &mut impl Rng) -> f64 {
+    let harmNumber = harmonicNumber(resolution);
+    let u: f64 = randGen.gen();
     let mut acc = 0.0;
     let mut result = 1.0;
 
@@ -133,4 +132,98 @@ pub fn harmonicDistribution() -> f64 {
         }
         result += 1.0;
     }
-}
\ No newline at end of file
+}
+
+// Cumulative probabilities of the values 1..=n when drawn with probability proportional to k^(-beta). With beta = 1 this is the Harmonic Distribution
+pub fn powerLawTable(n: f64, beta: f64) -> Vec<f64> {
+    let n = (n as i64).max(1);
+    let normalization: f64 = (1..=n).map(|k| (k as f64).powf(-beta)).sum();
+    let mut acc = 0.0;
+    (1..=n).map(|k| {
+        acc += (k as f64).powf(-beta) / normalization;
+        acc
+    }).collect()
+}
+
+// Generates a value in 1..=n from a table built by powerLawTable
+pub fn powerLawDistribution(table: &[f64], randGen: &mut impl Rng) -> f64 {
+    let u: f64 = randGen.gen();
+    (table.partition_point(|acc| *acc < u).min(table.len() - 1) + 1) as f64
+}
+
+// Derives an independent seed from a master seed and a stream number (SplitMix64), so every iteration and component of a run gets its own reproducible random source
+pub fn deriveSeed(masterSeed: u64, stream: u64) -> u64 {
+    let mut z = masterSeed.wrapping_add(stream.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/*
+ * Eigendecomposition of a symmetric matrix with the cyclic Jacobi method.
+ * Returns the eigenvalues and a matrix whose columns are the corresponding unit eigenvectors.
+ */
+pub fn symmetricEigen(matrix: &Array2<f64>) -> (Array1<f64>, Array2<f64>) {
+    let n = matrix.nrows();
+    let mut a = matrix.clone();
+    let mut vectors = Array2::<f64>::eye(n);
+    for _ in 0..100 {
+        let offDiagonal: f64 = (0..n).flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j))).map(|(i, j)| a[(i, j)].powi(2)).sum();
+        if offDiagonal < 1e-22 * a.iter().map(|x| x.powi(2)).sum::<f64>().max(f64::MIN_POSITIVE) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[(p, q)] == 0.0 {
+                    continue;
+                }
+                // Rotation that zeroes a[p][q]
+                let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                    a[(k, p)] = c * akp - s * akq;
+                    a[(k, q)] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                    a[(p, k)] = c * apk - s * aqk;
+                    a[(q, k)] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[(k, p)], vectors[(k, q)]);
+                    vectors[(k, p)] = c * vkp - s * vkq;
+                    vectors[(k, q)] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    (a.diag().to_owned(), vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn powerLawTableEndsAtOne() {
+        for n in [0.5, 1.0, 7.0, 100.0] {
+            let table = powerLawTable(n, 1.5);
+            assert_eq!(table.len(), (n as usize).max(1));
+            assert!((table.last().unwrap() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn powerLawWithBetaOneIsHarmonic() {
+        let table = powerLawTable(50.0, 1.0);
+        let mut powerLawGen = StdRng::seed_from_u64(3);
+        let mut harmonicGen = StdRng::seed_from_u64(3);
+        for _ in 0..1000 {
+            assert_eq!(powerLawDistribution(&table, &mut powerLawGen), harmonicDistribution(50.0, &mut harmonicGen));
+        }
+    }
+}
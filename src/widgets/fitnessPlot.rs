@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use druid::kurbo::{BezPath, Line};
+use druid::piet::{Text, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{Color, Point};
+
+// Best-so-far score of each iteration, as (generation, score) points in the order they were reached
+pub type FitnessCurves = Arc<Vec<Vec<(f64, f64)>>>;
+
+const MARGIN: f64 = 40.0;
+
+/*
+    Line chart of the best score so far against the generation, with one curve per iteration of the batch.
+    Axes are scaled to fit every curve, and the most recently started iteration is drawn on top in a stronger colour.
+ */
+pub struct FitnessPlot;
+
+impl FitnessPlot {
+    pub fn new() -> FitnessPlot {
+        FitnessPlot
+    }
+
+    fn curveColor(index: usize, highlighted: bool) -> Color {
+        let hue = (index as f64 * 137.5) % 360.0;
+        if highlighted {
+            Color::hlc(hue, 45.0, 70.0)
+        } else {
+            Color::hlc(hue, 70.0, 35.0).with_alpha(0.6)
+        }
+    }
+}
+
+impl Widget<FitnessCurves> for FitnessPlot {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut FitnessCurves, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _data: &FitnessCurves, _env: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, oldData: &FitnessCurves, data: &FitnessCurves, _env: &Env) {
+        if !Arc::ptr_eq(oldData, data) {
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &FitnessCurves, _env: &Env) -> Size {
+        bc.max()
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &FitnessCurves, _env: &Env) {
+        let size = ctx.size();
+        ctx.fill(size.to_rect(), &Color::WHITE);
+
+        let width = size.width - 2.0 * MARGIN;
+        let height = size.height - 2.0 * MARGIN;
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        let origin = Point::new(MARGIN, size.height - MARGIN);
+        ctx.stroke(Line::new(origin, Point::new(MARGIN, MARGIN)), &Color::BLACK, 1.0);
+        ctx.stroke(Line::new(origin, Point::new(size.width - MARGIN, origin.y)), &Color::BLACK, 1.0);
+
+        let points = data.iter().flatten();
+        let maxGeneration = points.clone().fold(1.0, |acc: f64, (g, _)| acc.max(*g));
+        let minScore = points.clone().fold(f64::INFINITY, |acc: f64, (_, s)| acc.min(*s)).min(0.0);
+        let maxScore = points.fold(f64::NEG_INFINITY, |acc: f64, (_, s)| acc.max(*s)).max(minScore + f64::EPSILON);
+        let toPoint = |(generation, score): (f64, f64)| Point::new(
+            origin.x + width * generation / maxGeneration,
+            origin.y - height * (score - minScore) / (maxScore - minScore)
+        );
+
+        // Curves are drawn as steps, since the best score only changes at improvement points
+        let lastIndex = data.iter().rposition(|curve| !curve.is_empty());
+        for (index, curve) in data.iter().enumerate() {
+            if curve.is_empty() {
+                continue;
+            }
+            let mut path = BezPath::new();
+            path.move_to(toPoint(curve[0]));
+            for window in curve.windows(2) {
+                path.line_to(toPoint((window[1].0, window[0].1)));
+                path.line_to(toPoint(window[1]));
+            }
+            let highlighted = Some(index) == lastIndex;
+            ctx.stroke(path, &FitnessPlot::curveColor(index, highlighted), if highlighted { 2.0 } else { 1.0 });
+        }
+
+        let labels = [
+            (format!("{}", (maxScore * 1000.0).round() / 1000.0), Point::new(2.0, MARGIN - 8.0)),
+            (format!("{}", (minScore * 1000.0).round() / 1000.0), Point::new(2.0, origin.y - 8.0)),
+            (format!("{}", maxGeneration), Point::new(size.width - MARGIN - 20.0, origin.y + 4.0)),
+            ("Best score by generation".to_string(), Point::new(MARGIN + 4.0, 4.0)),
+        ];
+        for (text, position) in labels {
+            if let Ok(layout) = ctx.text().new_text_layout(text).text_color(Color::BLACK).build() {
+                ctx.draw_text(&layout, position);
+            }
+        }
+    }
+}